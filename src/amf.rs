@@ -0,0 +1,416 @@
+use crate::types::{IndexedMesh, Vertex};
+use crate::units::Units;
+use crate::xml::{escape, XmlReader};
+use std::io::{Read, Result, Write};
+use std::ops::Range;
+
+// Unit names of the amf element, the first one of every unit is used when writing.
+// AMF has no centimeters, they are written as millimeters.
+const UNIT_NAMES: &[(Units, &[&str])] = &[
+    (Units::Micrometers, &["micron", "micrometer"]),
+    (Units::Millimeters, &["millimeter"]),
+    (Units::Meters, &["meter"]),
+    (Units::Inches, &["inch"]),
+    (Units::Feet, &["feet", "foot"]),
+];
+
+/// Material of an AMF file, referenced by the [volumes](struct.AmfVolume.html) of objects.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmfMaterial {
+    /// Id the volumes refer to.
+    pub id: usize,
+    /// Name given by the material's metadata, empty if there is none.
+    pub name: String,
+    /// Red, green and blue in `0.0..=1.0`, if the material has a constant color.
+    pub color: Option<[f32; 3]>,
+}
+
+/// Part of an AMF object made of a single material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmfVolume {
+    /// Id of the [material](struct.AmfMaterial.html), if any.
+    pub material_id: Option<usize>,
+    /// The faces of the object's mesh that belong to the volume.
+    pub faces: Range<usize>,
+}
+
+/// Object of an AMF file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmfObject {
+    /// Id of the object.
+    pub id: usize,
+    /// Name given by the object's metadata, empty if there is none.
+    pub name: String,
+    /// The vertices and faces of all volumes of the object.
+    pub mesh: IndexedMesh,
+    /// The volumes, in the order of their faces in the mesh.
+    pub volumes: Vec<AmfVolume>,
+}
+
+/// Contents of an AMF file, see [read_amf](fn.read_amf.html) and
+/// [write_amf](fn.write_amf.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmfModel {
+    /// Unit of all coordinates.
+    pub units: Units,
+    /// The objects, in the order of the file.
+    pub objects: Vec<AmfObject>,
+    /// The materials, in the order of the file.
+    pub materials: Vec<AmfMaterial>,
+}
+
+impl AmfModel {
+    /// Creates a model with `mesh` as its only object, made of a single volume without material.
+    pub fn from_mesh(mesh: IndexedMesh, units: Units) -> AmfModel {
+        let volumes = vec![AmfVolume {
+            material_id: None,
+            faces: 0..mesh.faces.len(),
+        }];
+        AmfModel {
+            units,
+            objects: vec![AmfObject {
+                id: 0,
+                name: String::new(),
+                mesh,
+                volumes,
+            }],
+            materials: Vec::new(),
+        }
+    }
+
+    /// Merges the meshes of all objects into one.
+    pub fn to_mesh(&self) -> IndexedMesh {
+        IndexedMesh::merge(self.objects.iter().map(|o| &o.mesh), None)
+    }
+}
+
+fn invalid(message: String) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("AMF: {}", message),
+    )
+}
+
+fn parse_text<T: ::std::str::FromStr>(reader: &mut XmlReader, name: &str) -> Result<T> {
+    let text = reader.text()?;
+    text.parse()
+        .map_err(|_| invalid(format!("invalid <{}>: {:?}", name, text)))
+}
+
+// Object while it is being read, the mesh is built when it ends.
+struct ObjectData {
+    id: usize,
+    name: String,
+    vertices: Vec<Vertex>,
+    faces: Vec<[usize; 3]>,
+    volumes: Vec<AmfVolume>,
+}
+
+fn parse_amf(xml: &str) -> Result<AmfModel> {
+    let mut model = AmfModel {
+        units: Units::Millimeters,
+        objects: Vec::new(),
+        materials: Vec::new(),
+    };
+    let mut object: Option<ObjectData> = None;
+    let mut material: Option<AmfMaterial> = None;
+    let mut volume: Option<(Option<usize>, usize)> = None;
+    let mut in_color = false;
+    let mut coordinates = [0.0; 3];
+    let mut triangle = [0; 3];
+    let mut color = [0.0; 3];
+    let mut reader = XmlReader::new(xml);
+    while let Some(element) = reader.next_element()? {
+        if element.end {
+            match element.name {
+                "object" => {
+                    if let Some(data) = object.take() {
+                        if let Some(t) = data
+                            .faces
+                            .iter()
+                            .find(|t| t.iter().any(|&v| v >= data.vertices.len()))
+                        {
+                            return Err(invalid(format!(
+                                "triangle {:?} of object {} out of range of its {} vertices",
+                                t,
+                                data.id,
+                                data.vertices.len()
+                            )));
+                        }
+                        model.objects.push(AmfObject {
+                            id: data.id,
+                            name: data.name,
+                            mesh: super::primitives::mesh_from_faces(data.vertices, data.faces),
+                            volumes: data.volumes,
+                        });
+                    }
+                }
+                "material" => model.materials.extend(material.take()),
+                "color" if in_color => {
+                    in_color = false;
+                    if let Some(material) = material.as_mut() {
+                        material.color = Some(color);
+                    }
+                }
+                "vertex" => {
+                    if let Some(data) = object.as_mut() {
+                        data.vertices.push(Vertex::new(coordinates));
+                    }
+                }
+                "triangle" => {
+                    if let Some(data) = object.as_mut() {
+                        data.faces.push(triangle);
+                    }
+                }
+                "volume" => {
+                    if let (Some(data), Some((material_id, start))) = (object.as_mut(), volume) {
+                        data.volumes.push(AmfVolume {
+                            material_id,
+                            faces: start..data.faces.len(),
+                        });
+                    }
+                    volume = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match element.name {
+            "amf" => {
+                if let Some(unit) = element.attribute("unit") {
+                    model.units = UNIT_NAMES
+                        .iter()
+                        .find(|(_, names)| names.contains(&unit))
+                        .map(|(u, _)| *u)
+                        .ok_or_else(|| invalid(format!("unknown unit {:?}", unit)))?;
+                }
+            }
+            "object" => {
+                object = Some(ObjectData {
+                    id: element.required("id")?,
+                    name: String::new(),
+                    vertices: Vec::new(),
+                    faces: Vec::new(),
+                    volumes: Vec::new(),
+                });
+            }
+            "material" => {
+                let new = AmfMaterial {
+                    id: element.required("id")?,
+                    name: String::new(),
+                    color: None,
+                };
+                if element.empty {
+                    model.materials.push(new);
+                } else {
+                    material = Some(new);
+                }
+            }
+            "metadata" if element.attribute("type") == Some("name") && volume.is_none() => {
+                let name = reader.text()?;
+                if let Some(material) = material.as_mut() {
+                    material.name = name;
+                } else if let Some(data) = object.as_mut() {
+                    data.name = name;
+                }
+            }
+            "color" if material.is_some() => {
+                in_color = true;
+                color = [0.0; 3];
+            }
+            "r" if in_color => color[0] = parse_text(&mut reader, "r")?,
+            "g" if in_color => color[1] = parse_text(&mut reader, "g")?,
+            "b" if in_color => color[2] = parse_text(&mut reader, "b")?,
+            "vertex" => coordinates = [0.0; 3],
+            "x" => coordinates[0] = parse_text(&mut reader, "x")?,
+            "y" => coordinates[1] = parse_text(&mut reader, "y")?,
+            "z" => coordinates[2] = parse_text(&mut reader, "z")?,
+            "volume" => {
+                let material_id = match element.attribute("materialid") {
+                    Some(_) => Some(element.required("materialid")?),
+                    None => None,
+                };
+                let start = object.as_ref().map_or(0, |data| data.faces.len());
+                volume = Some((material_id, start));
+            }
+            "v1" => triangle[0] = parse_text(&mut reader, "v1")?,
+            "v2" => triangle[1] = parse_text(&mut reader, "v2")?,
+            "v3" => triangle[2] = parse_text(&mut reader, "v3")?,
+            _ => {}
+        }
+    }
+    Ok(model)
+}
+
+/// Reads an AMF file, plain XML or compressed as zip archive.
+///
+/// Objects keep the split of their faces into volumes and the materials these refer to, with
+/// their name and constant color. Normals are computed from the triangles. Constellations,
+/// textures, edges for curved triangles, and color formulas are not supported. Reading
+/// compressed files needs the `zip` feature.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(br#"<?xml version="1.0"?>
+/// <amf unit="inch">
+///   <object id="1">
+///     <mesh>
+///       <vertices>
+///         <vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates></vertex>
+///         <vertex><coordinates><x>1</x><y>0</y><z>0</z></coordinates></vertex>
+///         <vertex><coordinates><x>0</x><y>1</y><z>0</z></coordinates></vertex>
+///       </vertices>
+///       <volume>
+///         <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+///       </volume>
+///     </mesh>
+///   </object>
+/// </amf>"#.to_vec());
+/// let model = stl_io::read_amf(&mut reader).unwrap();
+/// assert_eq!(model.units, stl_io::Units::Inches);
+/// assert_eq!(model.objects[0].mesh.faces.len(), 1);
+/// ```
+pub fn read_amf<R: Read>(read: &mut R) -> Result<AmfModel> {
+    let mut data = Vec::new();
+    read.read_to_end(&mut data)?;
+    if data.starts_with(b"PK\x03\x04") {
+        data = unzip_amf(data)?;
+    }
+    let xml = String::from_utf8(data).map_err(|e| invalid(e.to_string()))?;
+    parse_amf(&xml)
+}
+
+// Extracts the AMF document from a compressed AMF file.
+#[cfg(feature = "zip")]
+fn unzip_amf(data: Vec<u8>) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(::std::io::Cursor::new(data))?;
+    let name = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .min_by_key(|name| !name.to_ascii_lowercase().ends_with(".amf"))
+        .map(str::to_string)
+        .ok_or_else(|| invalid("empty zip archive".to_string()))?;
+    let mut xml = Vec::new();
+    archive.by_name(&name)?.read_to_end(&mut xml)?;
+    Ok(xml)
+}
+
+#[cfg(not(feature = "zip"))]
+fn unzip_amf(_: Vec<u8>) -> Result<Vec<u8>> {
+    Err(invalid(
+        "compressed files need the zip feature of stl_io".to_string(),
+    ))
+}
+
+/// Writes an AMF file as plain XML, see [write_amf_zip](fn.write_amf_zip.html) for the
+/// compressed variant. Models in centimeters are written in millimeters, since AMF does not
+/// support them.
+///
+/// ```
+/// use stl_io::{AmfModel, IndexedMesh, Units};
+/// let model = AmfModel::from_mesh(IndexedMesh::cube(1.0), Units::Millimeters);
+/// let mut amf = Vec::<u8>::new();
+/// stl_io::write_amf(&mut amf, &model).unwrap();
+/// let read = stl_io::read_amf(&mut ::std::io::Cursor::new(amf)).unwrap();
+/// assert_eq!(read, model);
+/// ```
+pub fn write_amf<W: Write>(writer: &mut W, model: &AmfModel) -> Result<()> {
+    for object in &model.objects {
+        if let Some(volume) = object
+            .volumes
+            .iter()
+            .find(|v| v.faces.start > v.faces.end || v.faces.end > object.mesh.faces.len())
+        {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!(
+                    "volume with faces {:?} of object {} out of range of its {} faces",
+                    volume.faces,
+                    object.id,
+                    object.mesh.faces.len()
+                ),
+            ));
+        }
+    }
+    let (unit, scale) = match UNIT_NAMES.iter().find(|(u, _)| *u == model.units) {
+        Some((_, names)) => (names[0], 1.0),
+        None => (
+            "millimeter",
+            model.units.conversion_factor(Units::Millimeters),
+        ),
+    };
+
+    let mut writer = ::std::io::BufWriter::new(writer);
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<amf unit="{}" version="1.1">"#, unit)?;
+    for material in &model.materials {
+        writeln!(writer, r#" <material id="{}">"#, material.id)?;
+        if !material.name.is_empty() {
+            writeln!(
+                writer,
+                r#"  <metadata type="name">{}</metadata>"#,
+                escape(&material.name)
+            )?;
+        }
+        if let Some([r, g, b]) = material.color {
+            writeln!(
+                writer,
+                "  <color><r>{}</r><g>{}</g><b>{}</b></color>",
+                r, g, b
+            )?;
+        }
+        writeln!(writer, " </material>")?;
+    }
+    for object in &model.objects {
+        writeln!(writer, r#" <object id="{}">"#, object.id)?;
+        if !object.name.is_empty() {
+            writeln!(
+                writer,
+                r#"  <metadata type="name">{}</metadata>"#,
+                escape(&object.name)
+            )?;
+        }
+        writeln!(writer, "  <mesh>")?;
+        writeln!(writer, "   <vertices>")?;
+        for v in &object.mesh.vertices {
+            writeln!(
+                writer,
+                "    <vertex><coordinates><x>{}</x><y>{}</y><z>{}</z></coordinates></vertex>",
+                v[0] * scale,
+                v[1] * scale,
+                v[2] * scale
+            )?;
+        }
+        writeln!(writer, "   </vertices>")?;
+        for volume in &object.volumes {
+            match volume.material_id {
+                Some(id) => writeln!(writer, r#"   <volume materialid="{}">"#, id)?,
+                None => writeln!(writer, "   <volume>")?,
+            }
+            for face in &object.mesh.faces[volume.faces.clone()] {
+                let [a, b, c] = face.vertices;
+                writeln!(
+                    writer,
+                    "    <triangle><v1>{}</v1><v2>{}</v2><v3>{}</v3></triangle>",
+                    a, b, c
+                )?;
+            }
+            writeln!(writer, "   </volume>")?;
+        }
+        writeln!(writer, "  </mesh>")?;
+        writeln!(writer, " </object>")?;
+    }
+    writeln!(writer, "</amf>")?;
+    writer.flush()
+}
+
+/// Writes a compressed AMF file, a zip archive holding the XML document as `model.amf`.
+#[cfg(feature = "zip")]
+pub fn write_amf_zip<W: Write + ::std::io::Seek>(writer: &mut W, model: &AmfModel) -> Result<()> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut archive = zip::ZipWriter::new(writer);
+    archive.start_file("model.amf", options)?;
+    write_amf(&mut archive, model)?;
+    archive.finish()?;
+    Ok(())
+}
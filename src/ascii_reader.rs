@@ -36,8 +36,8 @@ impl<'a> AsciiStlReader<'a> {
         }
     }
     /// Factory to create a new ascii STL Reader from read.
-    pub fn create_triangle_iterator(
-        read: &'a mut dyn ::std::io::Read,
+    pub fn create_triangle_iterator<T: ::std::io::Read + 'a>(
+        read: T,
     ) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>> {
         let mut lines = BufReader::new(read).lines();
         match lines.next() {
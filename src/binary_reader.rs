@@ -12,8 +12,8 @@ pub struct BinaryStlReader<'a> {
 
 impl<'a> BinaryStlReader<'a> {
     /// Factory to create a new BinaryStlReader from read.
-    pub fn create_triangle_iterator(
-        read: &'a mut dyn ::std::io::Read,
+    pub fn create_triangle_iterator<T: ::std::io::Read + 'a>(
+        read: T,
     ) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>> {
         let mut reader = Box::new(BufReader::new(read));
         let mut header = [0u8; 80];
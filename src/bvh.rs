@@ -0,0 +1,508 @@
+use crate::types::{IndexedMesh, Vector, Vertex};
+
+/// Half-line starting at `origin` and extending in `direction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// Start of the ray.
+    pub origin: Vertex,
+    /// Direction of the ray, does not need to be normalized.
+    pub direction: Vector<f32>,
+}
+
+/// Intersection of a [Ray](struct.Ray.html) with a face.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// Index of the face that was hit.
+    pub face: usize,
+    /// Ray parameter of the hit, i.e. `point = origin + distance * direction`. This is the
+    /// euclidean distance for normalized directions.
+    pub distance: f32,
+    /// The intersection point.
+    pub point: Vertex,
+    /// Whether the ray hit the back of the face, i.e. the side its winding points away from.
+    pub back_face: bool,
+}
+
+/// Point on a mesh closest to a query point, see
+/// [Bvh::closest_point](struct.Bvh.html#method.closest_point).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClosestPoint {
+    /// Index of the face the point lies on.
+    pub face: usize,
+    /// The closest point.
+    pub point: Vertex,
+    /// Euclidean distance between query and closest point.
+    pub distance: f32,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Node {
+    pub(crate) min: [f32; 3],
+    pub(crate) max: [f32; 3],
+    // For leaves the first face in the face order, otherwise the index of the left child, the
+    // right child follows it.
+    first: usize,
+    // Number of faces for leaves, zero for inner nodes.
+    count: usize,
+    // Sum of the area weighted normals of the faces below, and its first moment about the
+    // center of the box, for approximating their winding number from afar.
+    pub(crate) area_normal: [f64; 3],
+    pub(crate) moment: [[f64; 3]; 3],
+}
+
+impl Node {
+    pub(crate) fn center(&self) -> [f64; 3] {
+        [0, 1, 2].map(|i| (f64::from(self.min[i]) + f64::from(self.max[i])) / 2.0)
+    }
+}
+
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Bounding volume hierarchy over the faces of an [IndexedMesh](struct.IndexedMesh.html) for
+/// fast ray casting, closest point and overlap queries.
+///
+/// The hierarchy copies the triangles, so it does not borrow the mesh and can be shared
+/// between threads. It is built in O(n log n) by recursively splitting the faces at the median
+/// of their centroids along the longest axis.
+///
+/// ```
+/// use stl_io::{Bvh, IndexedMesh, IndexedTriangle, Normal, Ray, Vector, Vertex};
+/// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+/// let tetrahedron = IndexedMesh {
+///     vertices: vec![
+///         Vertex::new([0.0, 0.0, 0.0]),
+///         Vertex::new([1.0, 0.0, 0.0]),
+///         Vertex::new([0.0, 1.0, 0.0]),
+///         Vertex::new([0.0, 0.0, 1.0]),
+///     ],
+///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+/// };
+/// let bvh = Bvh::new(&tetrahedron);
+/// let ray = Ray {
+///     origin: Vertex::new([0.2, 0.2, -1.0]),
+///     direction: Vector::new([0.0, 0.0, 1.0]),
+/// };
+/// let hit = bvh.first_hit(&ray).unwrap();
+/// assert_eq!((hit.face, hit.distance), (0, 1.0));
+/// assert_eq!(bvh.all_hits(&ray).len(), 2);
+/// let closest = bvh.closest_point(Vertex::new([0.25, 0.25, -1.0])).unwrap();
+/// assert_eq!((closest.face, closest.distance), (0, 1.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    pub(crate) triangles: Vec<[Vertex; 3]>,
+    // Face indices, ordered such that every leaf covers a contiguous range.
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy over all faces of a mesh.
+    pub fn new(mesh: &IndexedMesh) -> Bvh {
+        let triangles = mesh
+            .faces
+            .iter()
+            .map(|f| f.vertices.map(|i| mesh.vertices[i]))
+            .collect::<Vec<_>>();
+        let centroids = triangles
+            .iter()
+            .map(|t| [0, 1, 2].map(|c| (t[0][c] + t[1][c] + t[2][c]) / 3.0))
+            .collect::<Vec<_>>();
+        let mut bvh = Bvh {
+            order: (0..triangles.len()).collect(),
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !bvh.order.is_empty() {
+            bvh.nodes.push(Node {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                first: 0,
+                count: 0,
+                area_normal: [0.0; 3],
+                moment: [[0.0; 3]; 3],
+            });
+            bvh.build(0, 0, bvh.order.len(), &centroids);
+        }
+        bvh
+    }
+
+    fn build(&mut self, node: usize, start: usize, end: usize, centroids: &[[f32; 3]]) {
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        let mut centroid_min = [f32::INFINITY; 3];
+        let mut centroid_max = [f32::NEG_INFINITY; 3];
+        for &fi in &self.order[start..end] {
+            for v in &self.triangles[fi] {
+                for c in 0..3 {
+                    min[c] = min[c].min(v[c]);
+                    max[c] = max[c].max(v[c]);
+                }
+            }
+            for c in 0..3 {
+                centroid_min[c] = centroid_min[c].min(centroids[fi][c]);
+                centroid_max[c] = centroid_max[c].max(centroids[fi][c]);
+            }
+        }
+        self.nodes[node].min = min;
+        self.nodes[node].max = max;
+        if end - start <= MAX_LEAF_SIZE {
+            self.nodes[node].first = start;
+            self.nodes[node].count = end - start;
+            let center = self.nodes[node].center();
+            let (mut area_normal, mut moment) = ([0.0; 3], [[0.0; 3]; 3]);
+            for &fi in &self.order[start..end] {
+                let [a, b, c] = self.triangles[fi].map(|v| v.0.map(f64::from));
+                let (u, v) = (
+                    [0, 1, 2].map(|i| b[i] - a[i]),
+                    [0, 1, 2].map(|i| c[i] - a[i]),
+                );
+                let normal = [
+                    (u[1] * v[2] - u[2] * v[1]) / 2.0,
+                    (u[2] * v[0] - u[0] * v[2]) / 2.0,
+                    (u[0] * v[1] - u[1] * v[0]) / 2.0,
+                ];
+                let offset = [0, 1, 2].map(|i| (a[i] + b[i] + c[i]) / 3.0 - center[i]);
+                for j in 0..3 {
+                    area_normal[j] += normal[j];
+                    for k in 0..3 {
+                        moment[j][k] += normal[j] * offset[k];
+                    }
+                }
+            }
+            self.nodes[node].area_normal = area_normal;
+            self.nodes[node].moment = moment;
+            return;
+        }
+
+        let axis = (0..3)
+            .max_by(|&a, &b| {
+                (centroid_max[a] - centroid_min[a]).total_cmp(&(centroid_max[b] - centroid_min[b]))
+            })
+            .unwrap();
+        let mid = (start + end) / 2;
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            centroids[a][axis].total_cmp(&centroids[b][axis])
+        });
+        let left = self.nodes.len();
+        for _ in 0..2 {
+            self.nodes.push(Node {
+                min: [0.0; 3],
+                max: [0.0; 3],
+                first: 0,
+                count: 0,
+                area_normal: [0.0; 3],
+                moment: [[0.0; 3]; 3],
+            });
+        }
+        self.nodes[node].first = left;
+        self.build(left, start, mid, centroids);
+        self.build(left + 1, mid, end, centroids);
+        let center = self.nodes[node].center();
+        let (mut area_normal, mut moment) = ([0.0; 3], [[0.0; 3]; 3]);
+        for child in &self.nodes[left..left + 2] {
+            let offset = [0, 1, 2].map(|i| child.center()[i] - center[i]);
+            for j in 0..3 {
+                area_normal[j] += child.area_normal[j];
+                for k in 0..3 {
+                    moment[j][k] += child.moment[j][k] + child.area_normal[j] * offset[k];
+                }
+            }
+        }
+        self.nodes[node].area_normal = area_normal;
+        self.nodes[node].moment = moment;
+    }
+
+    /// Returns the bounding box of the mesh, `None` if it has no faces.
+    pub fn bounds(&self) -> Option<(Vertex, Vertex)> {
+        self.nodes
+            .first()
+            .map(|n| (Vertex::new(n.min), Vertex::new(n.max)))
+    }
+
+    // Visits all leaves whose boxes pass the filter, calling visit for each face. The state is
+    // shared by both, so the filter can prune with what the visits found so far.
+    pub(crate) fn traverse<S, F, V>(&self, state: &mut S, filter: F, mut visit: V)
+    where
+        F: Fn(&S, &Node) -> bool,
+        V: FnMut(&mut S, usize),
+    {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(ni) = stack.pop() {
+            let node = &self.nodes[ni];
+            if !filter(state, node) {
+                continue;
+            }
+            if node.count > 0 {
+                for &fi in &self.order[node.first..node.first + node.count] {
+                    visit(state, fi);
+                }
+            } else {
+                stack.push(node.first);
+                stack.push(node.first + 1);
+            }
+        }
+    }
+
+    /// Returns the closest intersection of the ray with any face, hitting faces from both sides.
+    pub fn first_hit(&self, ray: &Ray) -> Option<RayHit> {
+        let mut best: Option<RayHit> = None;
+        let inverse = ray.direction.0.map(|c| 1.0 / c);
+        self.traverse(
+            &mut best,
+            |best, node| {
+                ray_box(ray, &inverse, node).is_some_and(|t| best.is_none_or(|b| t <= b.distance))
+            },
+            |best, fi| {
+                if let Some(hit) = ray_triangle(ray, &self.triangles[fi], fi) {
+                    if best.is_none_or(|b| hit.distance < b.distance) {
+                        *best = Some(hit);
+                    }
+                }
+            },
+        );
+        best
+    }
+
+    /// Returns all intersections of the ray with faces, sorted by distance.
+    pub fn all_hits(&self, ray: &Ray) -> Vec<RayHit> {
+        let mut hits = Vec::new();
+        let inverse = ray.direction.0.map(|c| 1.0 / c);
+        self.traverse(
+            &mut hits,
+            |_, node| ray_box(ray, &inverse, node).is_some(),
+            |hits, fi| hits.extend(ray_triangle(ray, &self.triangles[fi], fi)),
+        );
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.face.cmp(&b.face)));
+        hits
+    }
+
+    /// Returns the point on the mesh closest to `point`, `None` if the mesh has no faces.
+    pub fn closest_point(&self, point: Vertex) -> Option<ClosestPoint> {
+        let mut best: (Option<ClosestPoint>, f32) = (None, f32::INFINITY);
+        self.traverse(
+            &mut best,
+            |best, node| box_distance_squared(node, point) <= best.1,
+            |best, fi| {
+                let candidate = closest_point_on_triangle(point, &self.triangles[fi]);
+                let squared = distance_squared(point, candidate);
+                if squared < best.1 {
+                    *best = (
+                        Some(ClosestPoint {
+                            face: fi,
+                            point: candidate,
+                            distance: squared.sqrt(),
+                        }),
+                        squared,
+                    );
+                }
+            },
+        );
+        best.0
+    }
+
+    /// Returns all faces overlapping the axis aligned box from `min` to `max`, in ascending order.
+    pub fn faces_in_box(&self, min: Vertex, max: Vertex) -> Vec<usize> {
+        let mut faces = Vec::new();
+        self.traverse(
+            &mut faces,
+            |_, node| (0..3).all(|c| node.min[c] <= max[c] && node.max[c] >= min[c]),
+            |faces, fi| {
+                if triangle_box_overlap(&self.triangles[fi], min, max) {
+                    faces.push(fi);
+                }
+            },
+        );
+        faces.sort_unstable();
+        faces
+    }
+
+    /// Returns all faces within `radius` of `center`, in ascending order.
+    pub fn faces_in_sphere(&self, center: Vertex, radius: f32) -> Vec<usize> {
+        let mut faces = Vec::new();
+        let squared_radius = radius * radius;
+        self.traverse(
+            &mut faces,
+            |_, node| box_distance_squared(node, center) <= squared_radius,
+            |faces, fi| {
+                let closest = closest_point_on_triangle(center, &self.triangles[fi]);
+                if distance_squared(center, closest) <= squared_radius {
+                    faces.push(fi);
+                }
+            },
+        );
+        faces.sort_unstable();
+        faces
+    }
+}
+
+// Ray parameter where the ray enters the box, if it hits it at all.
+fn ray_box(ray: &Ray, inverse: &[f32; 3], node: &Node) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = f32::INFINITY;
+    for (c, &inverse) in inverse.iter().enumerate() {
+        let t1 = (node.min[c] - ray.origin[c]) * inverse;
+        let t2 = (node.max[c] - ray.origin[c]) * inverse;
+        // NaN appears for rays parallel to and within a slab, which does not restrict them.
+        let (near, far) = if t1 <= t2 { (t1, t2) } else { (t2, t1) };
+        if !near.is_nan() {
+            t_min = t_min.max(near);
+        }
+        if !far.is_nan() {
+            t_max = t_max.min(far);
+        }
+    }
+    // Allow for rounding errors, triangle tests are exact enough to sort out false positives.
+    if t_min <= t_max * (1.0 + 4.0 * f32::EPSILON) {
+        Some(t_min)
+    } else {
+        None
+    }
+}
+
+// Möller-Trumbore intersection, computed in f64.
+fn ray_triangle(ray: &Ray, triangle: &[Vertex; 3], face: usize) -> Option<RayHit> {
+    let v = triangle.map(|v| v.0.map(f64::from));
+    let o = ray.origin.0.map(f64::from);
+    let d = ray.direction.0.map(f64::from);
+    let sub = |a: [f64; 3], b: [f64; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    let cross = |a: [f64; 3], b: [f64; 3]| {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    };
+    let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+    let e1 = sub(v[1], v[0]);
+    let e2 = sub(v[2], v[0]);
+    let p = cross(d, e2);
+    let det = dot(e1, p);
+    if det == 0.0 {
+        return None;
+    }
+    let s = sub(o, v[0]);
+    let u = dot(s, p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = cross(s, e1);
+    let w = dot(d, q) / det;
+    if w < 0.0 || u + w > 1.0 {
+        return None;
+    }
+    let t = dot(e2, q) / det;
+    if t < 0.0 {
+        return None;
+    }
+    Some(RayHit {
+        face,
+        distance: t as f32,
+        point: Vertex::new([0, 1, 2].map(|c| (o[c] + t * d[c]) as f32)),
+        back_face: det < 0.0,
+    })
+}
+
+fn distance_squared(a: Vertex, b: Vertex) -> f32 {
+    (0..3).map(|c| (a[c] - b[c]) * (a[c] - b[c])).sum()
+}
+
+fn box_distance_squared(node: &Node, p: Vertex) -> f32 {
+    (0..3)
+        .map(|c| {
+            let d = (node.min[c] - p[c]).max(p[c] - node.max[c]).max(0.0);
+            d * d
+        })
+        .sum()
+}
+
+// Closest point on a triangle, following Ericson, Real-Time Collision Detection, 5.1.5.
+pub(crate) fn closest_point_on_triangle(p: Vertex, triangle: &[Vertex; 3]) -> Vertex {
+    use super::utils::{dot, sub};
+    let [a, b, c] = *triangle;
+    let along =
+        |base: Vertex, d: Vertex, t: f32| Vertex::new([0, 1, 2].map(|i| base[i] + t * d[i]));
+    let ab = sub(b, a);
+    let ac = sub(c, a);
+    let ap = sub(p, a);
+    let d1 = dot(ab, ap);
+    let d2 = dot(ac, ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = sub(p, b);
+    let d3 = dot(ab, bp);
+    let d4 = dot(ac, bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return along(a, ab, d1 / (d1 - d3));
+    }
+    let cp = sub(p, c);
+    let d5 = dot(ab, cp);
+    let d6 = dot(ac, cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return along(a, ac, d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return along(b, sub(c, b), (d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+    Vertex::new([0, 1, 2].map(|i| a[i] + ab[i] * v + ac[i] * w))
+}
+
+// Separating axis test of a triangle against an axis aligned box, touching counts as overlap.
+// Computed in f64, where the projections onto the box axes are exact, so that faces on the
+// boundary of a box reliably touch it.
+pub(crate) fn triangle_box_overlap(triangle: &[Vertex; 3], min: Vertex, max: Vertex) -> bool {
+    type Point = [f64; 3];
+    let sub = |a: Point, b: Point| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    let dot = |a: Point, b: Point| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let cross = |a: Point, b: Point| {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    };
+    let center = [0, 1, 2].map(|c| (f64::from(min[c]) + f64::from(max[c])) / 2.0);
+    let half = [0, 1, 2].map(|c| (f64::from(max[c]) - f64::from(min[c])) / 2.0);
+    let v = triangle.map(|p| sub(p.0.map(f64::from), center));
+    let edges = [sub(v[1], v[0]), sub(v[2], v[1]), sub(v[0], v[2])];
+    let unit = |c: usize| {
+        let mut axis = [0.0; 3];
+        axis[c] = 1.0;
+        axis
+    };
+
+    let mut axes = vec![cross(edges[0], edges[1])];
+    for c in 0..3 {
+        axes.push(unit(c));
+        for edge in edges {
+            axes.push(cross(unit(c), edge));
+        }
+    }
+    axes.into_iter().all(|axis| {
+        let projections = v.map(|p| dot(p, axis));
+        let radius = (0..3).map(|c| half[c] * axis[c].abs()).sum::<f64>();
+        let lo = projections.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = projections
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        lo <= radius && hi >= -radius
+    })
+}
@@ -0,0 +1,106 @@
+use crate::types::IndexedMesh;
+use std::collections::HashSet;
+
+/// Number of elements removed by [IndexedMesh::cleanup](struct.IndexedMesh.html#method.cleanup).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Faces that were collapsed or had zero area.
+    pub degenerate_faces: usize,
+    /// Faces that used the same three vertices as an earlier face.
+    pub duplicate_faces: usize,
+    /// Vertices that were not referenced by any face.
+    pub unreferenced_vertices: usize,
+}
+
+impl IndexedMesh {
+    /// Removes collapsed faces (faces referencing the same vertex more than once) and faces with
+    /// zero area, i.e. all faces [validate](#method.validate) would complain about.
+    /// Returns the number of removed faces.
+    pub fn remove_degenerate_faces(&mut self) -> usize {
+        let vertices = &self.vertices;
+        let before = self.faces.len();
+        self.faces.retain(|face| {
+            let [a, b, c] = face.vertices;
+            if a == b || b == c || c == a {
+                return false;
+            }
+            super::utils::tri_area(vertices[a], vertices[b], vertices[c]) >= f32::EPSILON
+        });
+        before - self.faces.len()
+    }
+
+    /// Removes faces that use the same three vertices as an earlier face. Faces with opposite
+    /// winding are considered duplicates as well, only the first occurrence is kept.
+    /// Returns the number of removed faces.
+    pub fn remove_duplicate_faces(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        let before = self.faces.len();
+        self.faces.retain(|face| {
+            let mut key = face.vertices;
+            key.sort_unstable();
+            seen.insert(key)
+        });
+        before - self.faces.len()
+    }
+
+    /// Removes all vertices that are not referenced by any face and compacts the face indices
+    /// accordingly. The relative order of the remaining vertices is preserved.
+    /// Returns the number of removed vertices.
+    pub fn remove_unreferenced_vertices(&mut self) -> usize {
+        let mut index_map = vec![None; self.vertices.len()];
+        for face in &self.faces {
+            for &vi in &face.vertices {
+                index_map[vi] = Some(0);
+            }
+        }
+        let mut vertices = Vec::with_capacity(self.vertices.len());
+        for (vi, new_index) in index_map.iter_mut().enumerate() {
+            if new_index.is_some() {
+                *new_index = Some(vertices.len());
+                vertices.push(self.vertices[vi]);
+            }
+        }
+        for face in &mut self.faces {
+            for vi in &mut face.vertices {
+                *vi = index_map[*vi].unwrap();
+            }
+        }
+        let removed = self.vertices.len() - vertices.len();
+        self.vertices = vertices;
+        removed
+    }
+
+    /// Runs all cleanup operations: removes degenerate faces, then duplicate faces and finally
+    /// all vertices that are no longer referenced.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let mut mesh = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([5.0, 5.0, 5.0]),
+    ///     ],
+    ///     faces: vec![
+    ///         IndexedTriangle { normal: Normal::new([0.0, 0.0, 1.0]), vertices: [0, 1, 2] },
+    ///         IndexedTriangle { normal: Normal::new([0.0, 0.0, -1.0]), vertices: [0, 2, 1] },
+    ///         IndexedTriangle { normal: Normal::new([0.0, 0.0, 1.0]), vertices: [0, 0, 1] },
+    ///     ],
+    /// };
+    /// let report = mesh.cleanup();
+    /// assert_eq!(report.degenerate_faces, 1);
+    /// assert_eq!(report.duplicate_faces, 1);
+    /// assert_eq!(report.unreferenced_vertices, 1);
+    /// ```
+    pub fn cleanup(&mut self) -> CleanupReport {
+        let degenerate_faces = self.remove_degenerate_faces();
+        let duplicate_faces = self.remove_duplicate_faces();
+        let unreferenced_vertices = self.remove_unreferenced_vertices();
+        CleanupReport {
+            degenerate_faces,
+            duplicate_faces,
+            unreferenced_vertices,
+        }
+    }
+}
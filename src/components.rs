@@ -0,0 +1,176 @@
+use crate::types::{IndexedMesh, IndexedTriangle, Vertex};
+use std::collections::HashMap;
+
+// Finds the representative of a set, compressing the path along the way.
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+impl IndexedMesh {
+    /// Splits the mesh into its connected components. Two faces belong to the same component
+    /// if they are connected through a chain of faces that share vertices.
+    ///
+    /// Components are ordered by their first face, faces and vertices keep their relative order.
+    /// Vertices not referenced by any face are dropped.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::new([0.0, 0.0, 1.0]), vertices };
+    /// let mesh = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([5.0, 0.0, 0.0]),
+    ///         Vertex::new([6.0, 0.0, 0.0]),
+    ///         Vertex::new([5.0, 1.0, 0.0]),
+    ///     ],
+    ///     faces: vec![face([0, 1, 2]), face([3, 4, 5])],
+    /// };
+    /// let parts = mesh.connected_components();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(IndexedMesh::merge(&parts, None), mesh);
+    /// ```
+    pub fn connected_components(&self) -> Vec<IndexedMesh> {
+        let mut parent = (0..self.vertices.len()).collect::<Vec<_>>();
+        for face in &self.faces {
+            let a = find(&mut parent, face.vertices[0]);
+            for &v in &face.vertices[1..] {
+                let b = find(&mut parent, v);
+                parent[b] = a;
+            }
+        }
+
+        let mut component_of_root = HashMap::new();
+        let mut component_faces: Vec<Vec<usize>> = Vec::new();
+        for (fi, face) in self.faces.iter().enumerate() {
+            let root = find(&mut parent, face.vertices[0]);
+            let ci = *component_of_root.entry(root).or_insert_with(|| {
+                component_faces.push(Vec::new());
+                component_faces.len() - 1
+            });
+            component_faces[ci].push(fi);
+        }
+
+        component_faces
+            .into_iter()
+            .map(|faces| {
+                // Copies only the vertices of this component, in their original order.
+                let mut used = faces
+                    .iter()
+                    .flat_map(|&fi| self.faces[fi].vertices)
+                    .collect::<Vec<_>>();
+                used.sort_unstable();
+                used.dedup();
+                let index_of = used
+                    .iter()
+                    .enumerate()
+                    .map(|(new, &old)| (old, new))
+                    .collect::<HashMap<_, _>>();
+                let faces = faces
+                    .into_iter()
+                    .map(|fi| {
+                        let face = self.faces[fi];
+                        IndexedTriangle {
+                            normal: face.normal,
+                            vertices: face.vertices.map(|v| index_of[&v]),
+                        }
+                    })
+                    .collect();
+                IndexedMesh {
+                    vertices: used.into_iter().map(|v| self.vertices[v]).collect(),
+                    faces,
+                }
+            })
+            .collect()
+    }
+
+    /// Concatenates several meshes into one, offsetting the face indices of every mesh by the
+    /// number of vertices before it.
+    ///
+    /// With `weld_tolerance` set, vertices closer than the tolerance to an already merged vertex
+    /// are welded to it, which reconnects parts that touch. `Some(0.0)` only welds vertices with
+    /// identical coordinates. Welding can produce collapsed faces, which
+    /// [cleanup](#method.cleanup) removes.
+    pub fn merge<'a, I>(meshes: I, weld_tolerance: Option<f32>) -> IndexedMesh
+    where
+        I: IntoIterator<Item = &'a IndexedMesh>,
+    {
+        let mut merged = IndexedMesh {
+            vertices: Vec::new(),
+            faces: Vec::new(),
+        };
+        let mut welder = weld_tolerance.map(Welder::new);
+        for mesh in meshes {
+            let index_map = mesh
+                .vertices
+                .iter()
+                .map(|&v| match welder.as_mut() {
+                    Some(welder) => welder.index(&mut merged.vertices, v),
+                    None => {
+                        merged.vertices.push(v);
+                        merged.vertices.len() - 1
+                    }
+                })
+                .collect::<Vec<_>>();
+            merged
+                .faces
+                .extend(mesh.faces.iter().map(|face| IndexedTriangle {
+                    normal: face.normal,
+                    vertices: face.vertices.map(|vi| index_map[vi]),
+                }));
+        }
+        merged
+    }
+}
+
+// Looks up vertices within a tolerance by hashing them into a grid with the tolerance as cell
+// size, so only the neighboring cells need to be searched.
+struct Welder {
+    tolerance: f32,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl Welder {
+    fn new(tolerance: f32) -> Self {
+        Welder {
+            tolerance,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, v: Vertex) -> [i64; 3] {
+        if self.tolerance > 0.0 {
+            v.0.map(|c| (c / self.tolerance).floor() as i64)
+        } else {
+            // Same trick as in as_indexed_triangles: identical bits, identical vertex.
+            v.0.map(|c| i64::from(c.to_bits()))
+        }
+    }
+
+    // Returns the index of a vertex within tolerance, adding v to vertices if there is none.
+    fn index(&mut self, vertices: &mut Vec<Vertex>, v: Vertex) -> usize {
+        let cell = self.cell(v);
+        let reach = if self.tolerance > 0.0 { 1 } else { 0 };
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    let neighbor = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                    for &vi in self.cells.get(&neighbor).into_iter().flatten() {
+                        let distance = super::utils::length(super::utils::sub(vertices[vi], v));
+                        if distance <= self.tolerance {
+                            return vi;
+                        }
+                    }
+                }
+            }
+        }
+        vertices.push(v);
+        self.cells.entry(cell).or_default().push(vertices.len() - 1);
+        vertices.len() - 1
+    }
+}
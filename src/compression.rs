@@ -0,0 +1,229 @@
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::types::Triangle;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use crate::TriangleIterator;
+#[cfg(any(feature = "gzip", feature = "zstd", feature = "zip"))]
+use std::io::{Read, Result, Seek};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+// Whether data starts with the signature of a compression, whether its feature is enabled or
+// not. Binary STL headers must not, or they would not be read back the same with every feature.
+pub(crate) fn looks_compressed(head: &[u8]) -> bool {
+    head.starts_with(GZIP_MAGIC) || head.starts_with(ZSTD_MAGIC)
+}
+
+// Compression of a STL file, recognized by its signature.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Compression {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+// Looks for the signature of a compression at the start of `read` and seeks back. Only enabled
+// compressions are recognized, otherwise the file is read as plain STL, as binary STL headers
+// may start with any bytes.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn detect_compression<R: Read + Seek>(read: &mut R) -> Result<Option<Compression>> {
+    let mut magic = Vec::with_capacity(4);
+    let maybe_read_error = (&mut *read).take(4).read_to_end(&mut magic);
+    read.seek(::std::io::SeekFrom::Start(0))?;
+    maybe_read_error?;
+    #[cfg(feature = "gzip")]
+    if magic.starts_with(GZIP_MAGIC) {
+        return Ok(Some(Compression::Gzip));
+    }
+    #[cfg(feature = "zstd")]
+    if magic.starts_with(ZSTD_MAGIC) {
+        return Ok(Some(Compression::Zstd));
+    }
+    Ok(None)
+}
+
+// Reader for the decompressed content.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn decompressing_reader<'a, R: Read>(
+    read: &'a mut R,
+    compression: Compression,
+) -> Result<Box<dyn Read + 'a>> {
+    match compression {
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(read))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(read)?)),
+    }
+}
+
+// Reads up to `len` bytes from the start of a stream, as far as it goes.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn read_head(read: &mut dyn Read, len: usize) -> Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(len);
+    read.take(len as u64).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+// Like create_stl_reader, for a stream that cannot seek back after probing for ascii STL.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn stream_stl_reader<'a>(
+    mut read: Box<dyn Read + 'a>,
+) -> Result<Box<dyn TriangleIterator<Item = Result<Triangle>> + 'a>> {
+    let head = read_head(&mut read, b"solid ".len())?;
+    let ascii = head == b"solid ";
+    let read = ::std::io::Cursor::new(head).chain(read);
+    if ascii {
+        super::ascii_reader::AsciiStlReader::create_triangle_iterator(read)
+    } else {
+        super::binary_reader::BinaryStlReader::create_triangle_iterator(read)
+    }
+}
+
+// Like read_stl_header, for the content of a compressed file.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn stream_stl_header(read: &mut dyn Read) -> Result<String> {
+    let head = read_head(read, 1024)?;
+    Ok(match head.strip_prefix(b"solid ") {
+        Some(rest) => {
+            let line = rest.split(|&b| b == b'\n').next().unwrap_or_default();
+            String::from_utf8_lossy(line).trim().to_string()
+        }
+        None => {
+            if head.len() < 80 {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::UnexpectedEof,
+                    "STL ends before the end of the header",
+                ));
+            }
+            String::from_utf8_lossy(&head[..80])
+                .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+                .to_string()
+        }
+    })
+}
+
+/// Writes a gzip compressed binary STL, e.g. for `.stl.gz` files, which
+/// [read_stl](fn.read_stl.html) reads transparently.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh::cube(1.0);
+/// let mut stl = Vec::<u8>::new();
+/// stl_io::write_stl_gzip(&mut stl, mesh.clone().into_triangle_vec().iter()).unwrap();
+/// let read = stl_io::read_stl(&mut ::std::io::Cursor::new(stl)).unwrap();
+/// assert_eq!(read.faces.len(), 12);
+/// ```
+#[cfg(feature = "gzip")]
+pub fn write_stl_gzip<T, W, I>(writer: &mut W, mesh: I) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = T>,
+    T: std::borrow::Borrow<Triangle>,
+{
+    let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    super::write_stl(&mut encoder, mesh)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Writes a zstd compressed binary STL, e.g. for `.stl.zst` files, which
+/// [read_stl](fn.read_stl.html) reads transparently.
+#[cfg(feature = "zstd")]
+pub fn write_stl_zstd<T, W, I>(writer: &mut W, mesh: I) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = T>,
+    T: std::borrow::Borrow<Triangle>,
+{
+    let mut encoder = zstd::stream::write::Encoder::new(writer, 0)?;
+    super::write_stl(&mut encoder, mesh)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Iterator over the STL files in a zip archive, see [read_stl_zip](fn.read_stl_zip.html).
+#[cfg(feature = "zip")]
+pub struct StlZipEntries<R> {
+    archive: zip::ZipArchive<R>,
+    entries: ::std::vec::IntoIter<usize>,
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek> Iterator for StlZipEntries<R> {
+    type Item = Result<(String, crate::types::IndexedMesh)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.entries.next()?;
+        let mut read_entry = || {
+            let mut file = self.archive.by_index(index)?;
+            let name = file.name().to_string();
+            // Entries cannot seek, which telling ascii from binary STL needs.
+            // The size in the archive is not trusted to reserve memory.
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            let mesh = super::read_stl(&mut ::std::io::Cursor::new(data))?;
+            Ok((name, mesh))
+        };
+        Some(read_entry())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: Read + Seek> ExactSizeIterator for StlZipEntries<R> {}
+
+/// Opens a zip archive and iterates over the STL files in it, by their names ending in `.stl`,
+/// `.stl.gz` or `.stl.zst` (ignoring case), in the order of the archive. Every item is the
+/// entry's path in the archive and its mesh, read like with [read_stl](fn.read_stl.html).
+///
+/// ```
+/// use stl_io::IndexedMesh;
+/// let mut bundle = ::std::io::Cursor::new(Vec::new());
+/// let cube = IndexedMesh::cube(1.0);
+/// stl_io::write_stl_zip(&mut bundle, &[("a.stl", &cube), ("parts/b.stl", &cube)]).unwrap();
+/// let names = stl_io::read_stl_zip(bundle)
+///     .unwrap()
+///     .map(|entry| entry.unwrap().0)
+///     .collect::<Vec<_>>();
+/// assert_eq!(names, ["a.stl", "parts/b.stl"]);
+/// ```
+#[cfg(feature = "zip")]
+pub fn read_stl_zip<R: Read + Seek>(read: R) -> Result<StlZipEntries<R>> {
+    let archive = zip::ZipArchive::new(read)?;
+    let entries = (0..archive.len())
+        .filter(|&i| {
+            archive.name_for_index(i).is_some_and(|name| {
+                let name = name.to_ascii_lowercase();
+                [".stl", ".stl.gz", ".stl.zst"]
+                    .iter()
+                    .any(|e| name.ends_with(e))
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(StlZipEntries {
+        archive,
+        entries: entries.into_iter(),
+    })
+}
+
+/// Writes meshes as binary STL files into a zip archive, each under the given path.
+#[cfg(feature = "zip")]
+pub fn write_stl_zip<W: ::std::io::Write + Seek>(
+    writer: &mut W,
+    entries: &[(&str, &crate::types::IndexedMesh)],
+) -> Result<()> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut archive = zip::ZipWriter::new(writer);
+    for (name, mesh) in entries {
+        archive.start_file(*name, options)?;
+        let triangles = (*mesh).clone().into_triangle_vec();
+        super::write_stl(&mut archive, triangles.iter())?;
+    }
+    archive.finish()?;
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use crate::slice::{assign_nesting, Contour, EdgeKey, Plane, PlaneIntersection};
+use crate::types::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+use std::collections::HashMap;
+
+impl IndexedMesh {
+    /// Cuts the mesh along a plane and returns the part below and the part above the plane.
+    ///
+    /// Faces crossing the plane are split, the new vertices on the plane are shared by both
+    /// halves. Vertices exactly on the plane are treated as in [slice](#method.slice).
+    /// With `cap` set, the cross-section of every closed contour, including its holes, is
+    /// triangulated and added to both halves, so that each half of a valid closed mesh passes
+    /// [validate](#method.validate) again.
+    /// A half that would only consist of faces lying in the plane, as when cutting exactly along
+    /// the top face of a box, is returned empty.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Plane, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let tetrahedron = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 1.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// let (below, above) = tetrahedron.cut(&Plane::horizontal(0.5), true);
+    /// assert!(below.validate().is_ok());
+    /// assert!(above.validate().is_ok());
+    /// assert_eq!(above.faces.len(), 4);
+    /// ```
+    pub fn cut(&self, plane: &Plane, cap: bool) -> (IndexedMesh, IndexedMesh) {
+        let intersection = PlaneIntersection::new(self, plane);
+        let above = |vi: usize| intersection.distances[vi] >= 0.0;
+
+        let mut vertices = self.vertices.clone();
+        let mut crossing: HashMap<EdgeKey, usize> = HashMap::new();
+        let mut split_vertex = |a: usize, b: usize| {
+            let key = (a.min(b), a.max(b));
+            *crossing.entry(key).or_insert_with(|| {
+                intersection.vertex_on_plane(key).unwrap_or_else(|| {
+                    vertices.push(intersection.point(self, key));
+                    vertices.len() - 1
+                })
+            })
+        };
+
+        let mut below_faces = Vec::new();
+        let mut above_faces = Vec::new();
+        for face in &self.faces {
+            let sides = face.vertices.map(above);
+            if sides[0] == sides[1] && sides[1] == sides[2] {
+                if sides[0] {
+                    above_faces.push(*face);
+                } else {
+                    below_faces.push(*face);
+                }
+                continue;
+            }
+            // Rotate the face so that the vertex alone on its side comes first.
+            let lone = (0..3)
+                .find(|&i| sides[i] != sides[(i + 1) % 3] && sides[i] != sides[(i + 2) % 3])
+                .unwrap();
+            let l = face.vertices[lone];
+            let p = face.vertices[(lone + 1) % 3];
+            let q = face.vertices[(lone + 2) % 3];
+            let x = split_vertex(l, p);
+            let y = split_vertex(q, l);
+            let (lone_side, other_side) = if sides[lone] {
+                (&mut above_faces, &mut below_faces)
+            } else {
+                (&mut below_faces, &mut above_faces)
+            };
+            let push = |faces: &mut Vec<IndexedTriangle>, vertices: [usize; 3]| {
+                // Vertices on the plane collapse some of the pieces.
+                if vertices[0] != vertices[1]
+                    && vertices[1] != vertices[2]
+                    && vertices[2] != vertices[0]
+                {
+                    faces.push(IndexedTriangle {
+                        normal: face.normal,
+                        vertices,
+                    });
+                }
+            };
+            push(lone_side, [l, x, y]);
+            push(other_side, [x, p, q]);
+            push(other_side, [x, q, y]);
+        }
+
+        if cap {
+            for [a, b, c] in cap_triangles(plane, &intersection, &crossing, &vertices) {
+                below_faces.push(IndexedTriangle {
+                    normal: plane.normal,
+                    vertices: [a, b, c],
+                });
+                above_faces.push(IndexedTriangle {
+                    normal: Normal::new(plane.normal.0.map(|c| -c)),
+                    vertices: [a, c, b],
+                });
+            }
+        }
+
+        let half = |faces: Vec<IndexedTriangle>| {
+            let mut mesh = IndexedMesh {
+                vertices: vertices.clone(),
+                faces,
+            };
+            mesh.remove_unreferenced_vertices();
+            if mesh.vertices.iter().all(|&v| plane.distance(v) == 0.0) {
+                mesh.vertices.clear();
+                mesh.faces.clear();
+            }
+            mesh
+        };
+        (half(below_faces), half(above_faces))
+    }
+}
+
+// Triangulates the cross-section bounded by the closed contours, as counter-clockwise triangles
+// when seen from above the plane.
+fn cap_triangles(
+    plane: &Plane,
+    intersection: &PlaneIntersection,
+    crossing: &HashMap<EdgeKey, usize>,
+    vertices: &[Vertex],
+) -> Vec<[usize; 3]> {
+    let mut loops: Vec<Vec<usize>> = Vec::new();
+    for (keys, closed) in &intersection.chains {
+        if !closed {
+            continue;
+        }
+        let mut indices: Vec<usize> = Vec::with_capacity(keys.len());
+        for key in keys {
+            let index = crossing[key];
+            if indices.last() != Some(&index) {
+                indices.push(index);
+            }
+        }
+        if indices.len() > 1 && indices[0] == indices[indices.len() - 1] {
+            indices.pop();
+        }
+        if indices.len() >= 3 {
+            loops.push(indices);
+        }
+    }
+    let mut contours = loops
+        .iter()
+        .map(|indices| Contour {
+            points: indices.iter().map(|&i| vertices[i]).collect(),
+            closed: true,
+            is_hole: false,
+            parent: None,
+        })
+        .collect::<Vec<_>>();
+    assign_nesting(plane, &mut contours);
+
+    let project = |indices: &[usize]| {
+        indices
+            .iter()
+            .map(|&i| plane.project(vertices[i]))
+            .collect::<Vec<_>>()
+    };
+    let mut triangles = Vec::new();
+    for (oi, outer) in contours.iter().enumerate() {
+        if outer.is_hole {
+            continue;
+        }
+        let holes = (0..contours.len())
+            .filter(|&hi| contours[hi].is_hole && contours[hi].parent == Some(oi))
+            .collect::<Vec<_>>();
+        let indices = loops[oi]
+            .iter()
+            .chain(holes.iter().flat_map(|&hi| loops[hi].iter()))
+            .copied()
+            .collect::<Vec<_>>();
+        let hole_points = holes
+            .iter()
+            .map(|&hi| project(&loops[hi]))
+            .collect::<Vec<_>>();
+        for triangle in super::triangulate::triangulate(&project(&loops[oi]), &hole_points) {
+            triangles.push(triangle.map(|i| indices[i]));
+        }
+    }
+    triangles
+}
@@ -0,0 +1,165 @@
+use crate::types::{IndexedMesh, Vertex};
+use std::f32::consts::PI;
+
+// Indices of a ring of points, ordered such that the polygon lies on their left, i.e.
+// counter-clockwise for outer boundaries and clockwise for holes.
+fn ring_order(points: &[[f32; 2]], offset: usize, counter_clockwise: bool) -> Vec<usize> {
+    let mut order = (offset..offset + points.len()).collect::<Vec<_>>();
+    if (super::utils::polygon_area(points) > 0.0) != counter_clockwise {
+        order.reverse();
+    }
+    order
+}
+
+impl IndexedMesh {
+    /// Extrudes a polygon with holes along the z axis, from `z = 0` to `z = height`.
+    ///
+    /// The polygon and its holes may be given in either orientation. Along the way, the polygon
+    /// is rotated around the z axis by up to `twist` radians and scaled around it by up to
+    /// `scale` (which should be positive) at the top, both growing linearly with the height.
+    /// The sides are split into `slices` (at least 1) layers, which only makes a difference for
+    /// twisted extrusions. The caps are triangulated, so the result is closed. Holes with fewer
+    /// than 3 points enclose nothing and are ignored.
+    ///
+    /// ```
+    /// let square = [[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]];
+    /// let hole = vec![[0.5, 0.5], [1.5, 0.5], [1.5, 1.5], [0.5, 1.5]];
+    /// let spacer = stl_io::IndexedMesh::extrude(&square, &[hole], 1.0, 0.0, 1.0, 1);
+    /// assert!(spacer.validate().is_ok());
+    /// assert_eq!(spacer.vertices.len(), 16);
+    /// ```
+    pub fn extrude(
+        outer: &[[f32; 2]],
+        holes: &[Vec<[f32; 2]>],
+        height: f32,
+        twist: f32,
+        scale: f32,
+        slices: usize,
+    ) -> IndexedMesh {
+        let slices = slices.max(1);
+        let holes = holes
+            .iter()
+            .filter(|hole| hole.len() >= 3)
+            .cloned()
+            .collect::<Vec<_>>();
+        let points = outer
+            .iter()
+            .chain(holes.iter().flatten())
+            .copied()
+            .collect::<Vec<_>>();
+        let n = points.len();
+        let mut vertices = Vec::with_capacity(n * (slices + 1));
+        for layer in 0..=slices {
+            let t = layer as f32 / slices as f32;
+            let (sin, cos) = (twist * t).sin_cos();
+            let s = 1.0 + (scale - 1.0) * t;
+            for p in &points {
+                vertices.push(Vertex::new([
+                    s * (cos * p[0] - sin * p[1]),
+                    s * (sin * p[0] + cos * p[1]),
+                    height * t,
+                ]));
+            }
+        }
+
+        let mut rings = vec![ring_order(outer, 0, true)];
+        let mut offset = outer.len();
+        for hole in &holes {
+            rings.push(ring_order(hole, offset, false));
+            offset += hole.len();
+        }
+        let mut faces = Vec::new();
+        for ring in &rings {
+            for k in 0..ring.len() {
+                let (p, q) = (ring[k], ring[(k + 1) % ring.len()]);
+                for layer in 0..slices {
+                    let (bottom, top) = (layer * n, (layer + 1) * n);
+                    faces.push([bottom + p, bottom + q, top + q]);
+                    faces.push([bottom + p, top + q, top + p]);
+                }
+            }
+        }
+        for [a, b, c] in super::triangulate::triangulate(outer, &holes) {
+            faces.push([a, c, b]);
+            faces.push([slices * n + a, slices * n + b, slices * n + c]);
+        }
+        super::primitives::mesh_from_faces(vertices, faces)
+    }
+
+    /// Revolves a closed profile around the z axis by `angle` radians, in `segments` (at least
+    /// 3 for a full turn, otherwise at least 1) steps.
+    ///
+    /// The profile is given as `[r, z]` points with `r >= 0`, in either orientation. Points on
+    /// the axis, with `r == 0`, are shared by all steps. A full turn (`angle >= 2π`) closes on
+    /// itself, smaller angles get the profile as cap at both ends. Positive angles turn
+    /// counter-clockwise seen from above, negative ones clockwise, with the faces pointing outwards
+    /// either way.
+    ///
+    /// ```
+    /// use std::f32::consts::PI;
+    /// // A ring with a square cross-section.
+    /// let profile = [[1.0, 0.0], [2.0, 0.0], [2.0, 1.0], [1.0, 1.0]];
+    /// let ring = stl_io::IndexedMesh::revolve(&profile, 2.0 * PI, 32);
+    /// assert!(ring.validate().is_ok());
+    /// let quarter = stl_io::IndexedMesh::revolve(&profile, PI / 2.0, 8);
+    /// assert!(quarter.validate().is_ok());
+    /// ```
+    pub fn revolve(profile: &[[f32; 2]], angle: f32, segments: usize) -> IndexedMesh {
+        let full = angle.abs() >= 2.0 * PI;
+        let segments = segments.max(if full { 3 } else { 1 });
+        let stations = if full { segments } else { segments + 1 };
+
+        // Points on the axis get a single vertex, the others one for every station.
+        let mut vertices = Vec::new();
+        let mut first_vertex = Vec::with_capacity(profile.len());
+        for p in profile {
+            first_vertex.push(vertices.len());
+            if p[0] == 0.0 {
+                vertices.push(Vertex::new([0.0, 0.0, p[1]]));
+                continue;
+            }
+            for station in 0..stations {
+                let phi = angle.clamp(-2.0 * PI, 2.0 * PI) * station as f32 / segments as f32;
+                vertices.push(Vertex::new([p[0] * phi.cos(), p[0] * phi.sin(), p[1]]));
+            }
+        }
+        let vertex = |p: usize, station: usize| {
+            if profile[p][0] == 0.0 {
+                first_vertex[p]
+            } else {
+                first_vertex[p] + station % stations
+            }
+        };
+
+        let mut faces = Vec::new();
+        let ring = ring_order(profile, 0, true);
+        for k in 0..ring.len() {
+            let (p, q) = (ring[k], ring[(k + 1) % ring.len()]);
+            for s in 0..segments {
+                if profile[q][0] != 0.0 {
+                    faces.push([vertex(p, s), vertex(q, s + 1), vertex(q, s)]);
+                }
+                if profile[p][0] != 0.0 {
+                    faces.push([vertex(p, s), vertex(p, s + 1), vertex(q, s + 1)]);
+                }
+            }
+        }
+        if !full {
+            for [a, b, c] in super::triangulate::triangulate(profile, &[]) {
+                faces.push([vertex(a, 0), vertex(b, 0), vertex(c, 0)]);
+                faces.push([
+                    vertex(a, segments),
+                    vertex(c, segments),
+                    vertex(b, segments),
+                ]);
+            }
+        }
+        if angle < 0.0 {
+            // Turning the other way mirrors the mesh, which turns it inside out.
+            for face in &mut faces {
+                face.swap(1, 2);
+            }
+        }
+        super::primitives::mesh_from_faces(vertices, faces)
+    }
+}
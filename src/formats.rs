@@ -0,0 +1,422 @@
+use crate::types::IndexedMesh;
+use std::io::{Cursor, Result, Write};
+use std::path::Path;
+
+/// A mesh file format that [FormatRegistry](struct.FormatRegistry.html) can dispatch to.
+///
+/// Formats work on whole files in memory. Only [name](#tymethod.name) and
+/// [extensions](#tymethod.extensions) are required, formats that cannot be recognized by their
+/// content, read or written keep the default implementations of the other methods.
+///
+/// ```
+/// use stl_io::{FormatRegistry, IndexedMesh, MeshFormat};
+/// use std::io::{Result, Write};
+///
+/// // Stores the debug output of the mesh, just enough for this example.
+/// struct Raw;
+///
+/// impl MeshFormat for Raw {
+///     fn name(&self) -> &str {
+///         "raw"
+///     }
+///     fn extensions(&self) -> &[&str] {
+///         &["raw"]
+///     }
+///     fn write(&self, writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+///         write!(writer, "{:?}", mesh)
+///     }
+/// }
+///
+/// let mut registry = FormatRegistry::default();
+/// registry.register(Raw);
+/// assert_eq!(registry.for_extension("RAW").unwrap().name(), "raw");
+/// ```
+pub trait MeshFormat {
+    /// Short name of the format, e.g. `"STL"`.
+    fn name(&self) -> &str;
+
+    /// File extensions of the format in lower case, without the leading dot. Compound
+    /// extensions like `"stl.gz"` take precedence over the last part of the file name.
+    fn extensions(&self) -> &[&str];
+
+    /// Whether `data`, the complete content of a file, looks like this format. Formats without
+    /// a recognizable signature return `false`, which is the default.
+    fn sniff(&self, _data: &[u8]) -> bool {
+        false
+    }
+
+    /// Reads a mesh from the complete content of a file. Fails with `Unsupported` by default.
+    fn read(&self, _data: &[u8]) -> Result<IndexedMesh> {
+        Err(unsupported(self.name(), "reading"))
+    }
+
+    /// Writes a mesh. Fails with `Unsupported` by default.
+    fn write(&self, _writer: &mut dyn Write, _mesh: &IndexedMesh) -> Result<()> {
+        Err(unsupported(self.name(), "writing"))
+    }
+}
+
+fn unsupported(name: &str, what: &str) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::Unsupported,
+        format!("{} does not support {}", name, what),
+    )
+}
+
+fn contains(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|w| w == needle)
+}
+
+fn starts_with_text(data: &[u8], text: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    data[start..].starts_with(text)
+}
+
+struct Stl;
+
+impl MeshFormat for Stl {
+    fn name(&self) -> &str {
+        "STL"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["stl"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        // Binary STL has no signature, but its size follows from the triangle count.
+        let binary = data.len() >= 84 && {
+            let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]);
+            84 + 50 * count as u64 == data.len() as u64
+        };
+        // Same check as the reader, which only takes files starting with "solid " as ascii.
+        binary || data.starts_with(b"solid ")
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        super::read_stl(&mut Cursor::new(data))
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        super::write_stl(&mut writer, mesh.clone().into_triangle_vec().iter())
+    }
+}
+
+// Compressed STL, recognized by the signature of the compression.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+struct CompressedStl(super::compression::Compression);
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+impl MeshFormat for CompressedStl {
+    fn name(&self) -> &str {
+        match self.0 {
+            #[cfg(feature = "gzip")]
+            super::compression::Compression::Gzip => "STL (gzip)",
+            #[cfg(feature = "zstd")]
+            super::compression::Compression::Zstd => "STL (zstd)",
+        }
+    }
+    fn extensions(&self) -> &[&str] {
+        match self.0 {
+            #[cfg(feature = "gzip")]
+            super::compression::Compression::Gzip => &["stl.gz"],
+            #[cfg(feature = "zstd")]
+            super::compression::Compression::Zstd => &["stl.zst"],
+        }
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        matches!(
+            super::compression::detect_compression(&mut Cursor::new(data)),
+            Ok(Some(compression)) if compression == self.0
+        )
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        super::read_stl(&mut Cursor::new(data))
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        let triangles = mesh.clone().into_triangle_vec();
+        match self.0 {
+            #[cfg(feature = "gzip")]
+            super::compression::Compression::Gzip => {
+                super::write_stl_gzip(&mut writer, triangles.iter())
+            }
+            #[cfg(feature = "zstd")]
+            super::compression::Compression::Zstd => {
+                super::write_stl_zstd(&mut writer, triangles.iter())
+            }
+        }
+    }
+}
+
+struct Obj;
+
+impl MeshFormat for Obj {
+    fn name(&self) -> &str {
+        "OBJ"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        super::read_obj(&mut Cursor::new(data))
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        super::write_obj(&mut writer, mesh)
+    }
+}
+
+struct Ply;
+
+impl MeshFormat for Ply {
+    fn name(&self) -> &str {
+        "PLY"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["ply"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        data.starts_with(b"ply\n") || data.starts_with(b"ply\r\n")
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        super::read_ply(&mut Cursor::new(data))
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        super::write_ply(&mut writer, mesh, super::PlyFormat::BinaryLittleEndian)
+    }
+}
+
+struct Off;
+
+impl MeshFormat for Off {
+    fn name(&self) -> &str {
+        "OFF"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["off"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        let first = data
+            .split(|b| b.is_ascii_whitespace())
+            .find(|token| !token.is_empty());
+        matches!(
+            first,
+            Some(b"OFF") | Some(b"COFF") | Some(b"NOFF") | Some(b"CNOFF")
+        )
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        super::read_off(&mut Cursor::new(data))
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        super::write_off(&mut writer, mesh)
+    }
+}
+
+struct Amf;
+
+impl MeshFormat for Amf {
+    fn name(&self) -> &str {
+        "AMF"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["amf"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        if data.starts_with(b"PK\x03\x04") {
+            return contains(data, b".amf");
+        }
+        starts_with_text(data, b"<") && contains(&data[..data.len().min(1024)], b"<amf")
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        Ok(super::read_amf(&mut Cursor::new(data))?.to_mesh())
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        let model = super::AmfModel::from_mesh(mesh.clone(), super::Units::Millimeters);
+        super::write_amf(&mut writer, &model)
+    }
+}
+
+#[cfg(feature = "threemf")]
+struct ThreeMf;
+
+#[cfg(feature = "threemf")]
+impl MeshFormat for ThreeMf {
+    fn name(&self) -> &str {
+        "3MF"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["3mf"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        data.starts_with(b"PK\x03\x04") && contains(data, b".model")
+    }
+    fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+        Ok(super::read_3mf(&mut Cursor::new(data))?.to_mesh())
+    }
+    fn write(&self, writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        // The zip container needs to seek, so it is assembled in memory.
+        let model = super::ThreeMfModel::from_mesh(mesh.clone(), super::Units::Millimeters);
+        let mut buffer = Cursor::new(Vec::new());
+        super::write_3mf(&mut buffer, &model)?;
+        writer.write_all(buffer.get_ref())
+    }
+}
+
+struct Glb;
+
+impl MeshFormat for Glb {
+    fn name(&self) -> &str {
+        "GLB"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["glb"]
+    }
+    fn sniff(&self, data: &[u8]) -> bool {
+        data.starts_with(b"glTF")
+    }
+    fn write(&self, mut writer: &mut dyn Write, mesh: &IndexedMesh) -> Result<()> {
+        super::write_glb(&mut writer, mesh, true)
+    }
+}
+
+/// Set of [formats](trait.MeshFormat.html) to load and save meshes by file extension and
+/// content.
+///
+/// The default registry knows STL, compressed as `.stl.gz` or `.stl.zst` with the `gzip` or
+/// `zstd` feature, OBJ, PLY, OFF, AMF, 3MF (with the `threemf` feature) and, only for writing,
+/// GLB. Applications can [register](#method.register) more formats, which
+/// take precedence over the ones registered before, so they can also replace built-in ones.
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn MeshFormat>>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        let mut registry = FormatRegistry::new();
+        registry.register(Glb);
+        #[cfg(feature = "threemf")]
+        registry.register(ThreeMf);
+        registry.register(Amf);
+        registry.register(Off);
+        registry.register(Ply);
+        registry.register(Obj);
+        registry.register(Stl);
+        #[cfg(feature = "gzip")]
+        registry.register(CompressedStl(super::compression::Compression::Gzip));
+        #[cfg(feature = "zstd")]
+        registry.register(CompressedStl(super::compression::Compression::Zstd));
+        registry
+    }
+}
+
+impl FormatRegistry {
+    /// Creates a registry without any formats, see [default](#method.default) for one with the
+    /// built-in formats.
+    pub fn new() -> FormatRegistry {
+        FormatRegistry {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Adds a format, which takes precedence over all formats registered before.
+    pub fn register<F: MeshFormat + 'static>(&mut self, format: F) {
+        self.formats.insert(0, Box::new(format));
+    }
+
+    /// The registered formats, by precedence.
+    pub fn formats(&self) -> impl Iterator<Item = &dyn MeshFormat> {
+        self.formats.iter().map(|f| f.as_ref())
+    }
+
+    /// Finds the format for a file extension, ignoring case.
+    pub fn for_extension(&self, extension: &str) -> Option<&dyn MeshFormat> {
+        let extension = extension.to_ascii_lowercase();
+        self.formats()
+            .find(|f| f.extensions().contains(&extension.as_str()))
+    }
+
+    // Extension of a file name, the longest one of any format that matches, so that
+    // "part.stl.gz" gets "stl.gz" rather than "gz".
+    fn extension_of(&self, path: &Path) -> Option<String> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        self.formats()
+            .flat_map(|f| f.extensions().iter())
+            .filter(|e| {
+                name.strip_suffix(*e)
+                    .and_then(|stem| stem.strip_suffix('.'))
+                    .is_some_and(|stem| !stem.is_empty())
+            })
+            .max_by_key(|e| e.len())
+            .map(|e| e.to_string())
+            .or_else(|| path.extension()?.to_str().map(str::to_string))
+    }
+
+    /// Finds the format of a file from its content and extension, if known.
+    ///
+    /// A format whose extension matches and that recognizes the content wins, then any format
+    /// that recognizes the content, so that mislabeled files still load, and then one whose
+    /// extension matches.
+    pub fn detect(&self, data: &[u8], extension: Option<&str>) -> Option<&dyn MeshFormat> {
+        let by_extension = extension.and_then(|e| self.for_extension(e));
+        if let Some(format) = by_extension.filter(|f| f.sniff(data)) {
+            return Some(format);
+        }
+        self.formats().find(|f| f.sniff(data)).or(by_extension)
+    }
+
+    /// Reads a mesh from the complete content of a file, see [detect](#method.detect).
+    pub fn read(&self, data: &[u8], extension: Option<&str>) -> Result<IndexedMesh> {
+        let format = self.detect(data, extension).ok_or_else(|| {
+            ::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidData,
+                match extension {
+                    Some(extension) => {
+                        format!("unknown mesh format with extension {:?}", extension)
+                    }
+                    None => "unknown mesh format".to_string(),
+                },
+            )
+        })?;
+        format.read(data)
+    }
+
+    /// Loads a mesh from a file, in the format detected from its content and extension.
+    pub fn load<P: AsRef<Path>>(&self, path: P) -> Result<IndexedMesh> {
+        let path = path.as_ref();
+        let data = ::std::fs::read(path)?;
+        self.read(&data, self.extension_of(path).as_deref())
+    }
+
+    /// Saves a mesh to a file, in the format given by its extension. The mesh is encoded in
+    /// memory first, so if the format fails, an existing file is left as it was.
+    pub fn save<P: AsRef<Path>>(&self, path: P, mesh: &IndexedMesh) -> Result<()> {
+        let path = path.as_ref();
+        let format = self
+            .extension_of(path)
+            .and_then(|e| self.for_extension(&e))
+            .ok_or_else(|| {
+                ::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidInput,
+                    format!("no mesh format for {:?}", path),
+                )
+            })?;
+        let mut data = Vec::new();
+        format.write(&mut data, mesh)?;
+        ::std::fs::write(path, data)
+    }
+}
+
+/// Loads a mesh from a file in any of the formats of the
+/// [default registry](struct.FormatRegistry.html), recognized by content and extension.
+///
+/// ```rust,no_run
+/// let mesh = stl_io::load_mesh("part.ply").unwrap();
+/// stl_io::save_mesh("part.stl", &mesh).unwrap();
+/// ```
+pub fn load_mesh<P: AsRef<Path>>(path: P) -> Result<IndexedMesh> {
+    FormatRegistry::default().load(path)
+}
+
+/// Saves a mesh to a file in the format given by its extension, see
+/// [load_mesh](fn.load_mesh.html).
+pub fn save_mesh<P: AsRef<Path>>(path: P, mesh: &IndexedMesh) -> Result<()> {
+    FormatRegistry::default().save(path, mesh)
+}
@@ -0,0 +1,225 @@
+use crate::types::{IndexedMesh, Vertex};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{Result, Write};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// Quaternion of the rotation by -90° about x, which turns z up into y up.
+const Z_UP_TO_Y_UP: &str = "[-0.70710677,0,0,0.70710677]";
+
+// Buffer and JSON document of a glTF asset. Every piece of the buffer is a multiple of 4 bytes
+// long, so all views stay aligned.
+fn gltf_parts(
+    mesh: &IndexedMesh,
+    flat_normals: bool,
+    uri: Option<&str>,
+) -> Result<(String, Vec<u8>)> {
+    if mesh.faces.is_empty() {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            "glTF needs at least one triangle",
+        ));
+    }
+    if mesh.vertices.len() > u32::MAX as usize {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("too many vertices for glTF: {}", mesh.vertices.len()),
+        ));
+    }
+
+    // Flat normals need a vertex per corner, as glTF only knows vertex normals.
+    let positions: Vec<Vertex> = if flat_normals {
+        mesh.faces
+            .iter()
+            .flat_map(|f| f.vertices.map(|v| mesh.vertices[v]))
+            .collect()
+    } else {
+        mesh.vertices.clone()
+    };
+    if let Some(p) = positions
+        .iter()
+        .find(|p| !p.0.iter().all(|c| c.is_finite()))
+    {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("glTF needs finite vertex coordinates, got {:?}", p),
+        ));
+    }
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    let mut buffer = Vec::new();
+    for p in &positions {
+        for c in 0..3 {
+            min[c] = min[c].min(p[c]);
+            max[c] = max[c].max(p[c]);
+            buffer.write_f32::<LittleEndian>(p[c])?;
+        }
+    }
+    let positions_length = buffer.len();
+    if flat_normals {
+        for face in &mesh.faces {
+            let [a, b, c] = face.vertices.map(|v| mesh.vertices[v]);
+            let n = super::utils::normal(a, b, c);
+            // glTF requires unit normals, degenerate faces get an arbitrary one.
+            let n = if super::utils::length(n) > 0.5 {
+                n
+            } else {
+                Vertex::new([0.0, 0.0, 1.0])
+            };
+            for _ in 0..3 {
+                for c in 0..3 {
+                    buffer.write_f32::<LittleEndian>(n[c])?;
+                }
+            }
+        }
+    } else {
+        for face in &mesh.faces {
+            for &v in &face.vertices {
+                buffer.write_u32::<LittleEndian>(v as u32)?;
+            }
+        }
+    }
+    let second_length = buffer.len() - positions_length;
+
+    let bounds = |b: [f32; 3]| format!("[{},{},{}]", b[0], b[1], b[2]);
+    let (second_view, second_accessor, primitive) = if flat_normals {
+        (
+            format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+                positions_length, second_length, ARRAY_BUFFER
+            ),
+            format!(
+                r#"{{"bufferView":1,"componentType":{},"count":{},"type":"VEC3"}}"#,
+                FLOAT,
+                positions.len()
+            ),
+            r#"{"attributes":{"POSITION":0,"NORMAL":1},"mode":4}"#,
+        )
+    } else {
+        (
+            format!(
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+                positions_length, second_length, ELEMENT_ARRAY_BUFFER
+            ),
+            format!(
+                r#"{{"bufferView":1,"componentType":{},"count":{},"type":"SCALAR"}}"#,
+                UNSIGNED_INT,
+                mesh.faces.len() * 3
+            ),
+            r#"{"attributes":{"POSITION":0},"indices":1,"mode":4}"#,
+        )
+    };
+    let buffer_json = match uri {
+        Some(uri) => format!(
+            r#"{{"byteLength":{},"uri":"{}"}}"#,
+            buffer.len(),
+            escape_json(uri)
+        ),
+        None => format!(r#"{{"byteLength":{}}}"#, buffer.len()),
+    };
+    let json = format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"stl_io"}},"#,
+            r#""scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"rotation":{}}}],"#,
+            r#""meshes":[{{"primitives":[{}]}}],"#,
+            r#""accessors":[{{"bufferView":0,"componentType":{},"count":{},"type":"VEC3","min":{},"max":{}}},{}],"#,
+            r#""bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":{},"target":{}}},{}],"#,
+            r#""buffers":[{}]}}"#
+        ),
+        Z_UP_TO_Y_UP,
+        primitive,
+        FLOAT,
+        positions.len(),
+        bounds(min),
+        bounds(max),
+        second_accessor,
+        positions_length,
+        ARRAY_BUFFER,
+        second_view,
+        buffer_json
+    );
+    Ok((json, buffer))
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a mesh as binary glTF 2.0 (`.glb`), e.g. for previews in web viewers.
+///
+/// The result holds a single node with one triangle primitive. Vertex positions get their
+/// bounds, as required by the spec. With `flat_normals`, every triangle gets its own three
+/// vertices with the face normal, computed from the vertices, so it is shaded flat like in
+/// STL viewers. Otherwise the vertices are shared and indexed, and viewers compute the normals.
+///
+/// STL has no fixed orientation, but is usually modelled with z pointing up, while glTF viewers
+/// take y as up. The node therefore rotates the mesh by -90° about the x axis, so that parts
+/// stand upright in viewers, the positions in the buffer are those of the mesh.
+///
+/// Fails for meshes without triangles, which glTF cannot express, and for vertices with infinite
+/// or NaN coordinates, which would make the bounds invalid JSON.
+///
+/// ```
+/// let mut glb = Vec::new();
+/// stl_io::write_glb(&mut glb, &stl_io::IndexedMesh::cube(1.0), true).unwrap();
+/// assert_eq!(&glb[..4], b"glTF");
+/// assert_eq!(glb.len() % 4, 0);
+/// ```
+pub fn write_glb<W: Write>(writer: &mut W, mesh: &IndexedMesh, flat_normals: bool) -> Result<()> {
+    let (json, buffer) = gltf_parts(mesh, flat_normals, None)?;
+    let mut json = json.into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    let length = 12 + 8 + json.len() + 8 + buffer.len();
+    if length > u32::MAX as usize {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("mesh too large for GLB: {} bytes", length),
+        ));
+    }
+    writer.write_u32::<LittleEndian>(GLB_MAGIC)?;
+    writer.write_u32::<LittleEndian>(GLB_VERSION)?;
+    writer.write_u32::<LittleEndian>(length as u32)?;
+    writer.write_u32::<LittleEndian>(json.len() as u32)?;
+    writer.write_u32::<LittleEndian>(CHUNK_JSON)?;
+    writer.write_all(&json)?;
+    writer.write_u32::<LittleEndian>(buffer.len() as u32)?;
+    writer.write_u32::<LittleEndian>(CHUNK_BIN)?;
+    writer.write_all(&buffer)?;
+    writer.flush()
+}
+
+/// Writes a mesh as glTF 2.0 with separate JSON (`.gltf`) and buffer (`.bin`) files, see
+/// [write_glb](fn.write_glb.html). `bin_uri` is the URI of the buffer file relative to the
+/// JSON file, usually just its file name, and should already be percent-encoded.
+pub fn write_gltf<W: Write, B: Write>(
+    gltf_writer: &mut W,
+    bin_writer: &mut B,
+    bin_uri: &str,
+    mesh: &IndexedMesh,
+    flat_normals: bool,
+) -> Result<()> {
+    let (json, buffer) = gltf_parts(mesh, flat_normals, Some(bin_uri))?;
+    gltf_writer.write_all(json.as_bytes())?;
+    gltf_writer.flush()?;
+    bin_writer.write_all(&buffer)?;
+    bin_writer.flush()
+}
@@ -0,0 +1,102 @@
+use crate::types::{IndexedMesh, Vertex};
+use std::io::Result;
+
+impl IndexedMesh {
+    /// Creates a closed relief from a `width` x `height` grid of heights, as used for terrain
+    /// and lithophanes.
+    ///
+    /// `heights` holds the rows one after another, sample `(i, j)` is at index
+    /// `j * width + i` and becomes the vertex at `x = i * spacing[0]`, `y = j * spacing[1]`.
+    /// Note that images usually store their top row first, which ends up at `y = 0`.
+    /// The top surface lies at `z = base + z_scale * heights[..]`, above a flat bottom at `z = 0`
+    /// and surrounded by vertical walls. For grayscale images, the pixel values scaled to
+    /// `0.0..=1.0` can be used as heights directly.
+    ///
+    /// Fails if `heights` does not have `width * height` values, if there are less than two
+    /// samples in either direction, or if the top surface does not stay above the bottom.
+    ///
+    /// ```
+    /// let heights = [0.0, 1.0, 0.0, 1.0, 2.0, 1.0];
+    /// let relief = stl_io::IndexedMesh::from_heightmap(&heights, 3, 2, [1.0, 1.0], 0.5, 1.0)
+    ///     .unwrap();
+    /// assert!(relief.validate().is_ok());
+    /// let (_, max) = relief.bounding_box().unwrap();
+    /// assert_eq!(max[2], 2.0);
+    /// ```
+    pub fn from_heightmap(
+        heights: &[f32],
+        width: usize,
+        height: usize,
+        spacing: [f32; 2],
+        z_scale: f32,
+        base: f32,
+    ) -> Result<IndexedMesh> {
+        if width < 2 || height < 2 || heights.len() != width * height {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!(
+                    "expected at least 2 x 2 heights and {} x {} = {} heights, got {}",
+                    width,
+                    height,
+                    width * height,
+                    heights.len()
+                ),
+            ));
+        }
+        let mut vertices = Vec::with_capacity(width * height + 2 * (width + height));
+        for (k, &h) in heights.iter().enumerate() {
+            let z = base + z_scale * h;
+            if z <= 0.0 || z.is_nan() {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "height #{} gives the top surface at z = {}, below the bottom",
+                        k, z
+                    ),
+                ));
+            }
+            vertices.push(Vertex::new([
+                (k % width) as f32 * spacing[0],
+                (k / width) as f32 * spacing[1],
+                z,
+            ]));
+        }
+        let top = |i: usize, j: usize| j * width + i;
+
+        let mut faces = Vec::new();
+        for j in 0..height - 1 {
+            for i in 0..width - 1 {
+                faces.push([top(i, j), top(i + 1, j), top(i + 1, j + 1)]);
+                faces.push([top(i, j), top(i + 1, j + 1), top(i, j + 1)]);
+            }
+        }
+
+        // The border of the grid, counter-clockwise when seen from above.
+        let border = (0..width - 1)
+            .map(|i| (i, 0))
+            .chain((0..height - 1).map(|j| (width - 1, j)))
+            .chain((1..width).rev().map(|i| (i, height - 1)))
+            .chain((1..height).rev().map(|j| (0, j)))
+            .collect::<Vec<_>>();
+        let first_bottom = vertices.len();
+        for &(i, j) in &border {
+            let p = vertices[top(i, j)];
+            vertices.push(Vertex::new([p[0], p[1], 0.0]));
+        }
+        let n = border.len();
+        for k in 0..n {
+            let (p, q) = (border[k], border[(k + 1) % n]);
+            let (bottom_p, bottom_q) = (first_bottom + k, first_bottom + (k + 1) % n);
+            faces.push([bottom_p, bottom_q, top(q.0, q.1)]);
+            faces.push([bottom_p, top(q.0, q.1), top(p.0, p.1)]);
+        }
+        let outline = border
+            .iter()
+            .map(|&(i, j)| [i as f32 * spacing[0], j as f32 * spacing[1]])
+            .collect::<Vec<_>>();
+        for [a, b, c] in super::triangulate::triangulate(&outline, &[]) {
+            faces.push([first_bottom + a, first_bottom + c, first_bottom + b]);
+        }
+        Ok(super::primitives::mesh_from_faces(vertices, faces))
+    }
+}
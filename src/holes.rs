@@ -0,0 +1,130 @@
+use crate::types::{IndexedMesh, IndexedTriangle, Vertex};
+use std::collections::HashMap;
+
+impl IndexedMesh {
+    /// Finds all holes in the mesh, i.e. cycles of half-edges without twin in the mesh's
+    /// [Topology](struct.Topology.html).
+    ///
+    /// Each hole is returned as an ordered cycle of vertex indices. The cycle follows the
+    /// direction in which the adjacent faces traverse the boundary edges, the closing edge from
+    /// the last back to the first vertex is implied.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let mesh = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///     ],
+    ///     faces: vec![face([0, 1, 2]), face([0, 2, 3])],
+    /// };
+    /// assert_eq!(mesh.boundary_loops(), vec![vec![0, 1, 2, 3]]);
+    /// ```
+    pub fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let topology = self.topology();
+        let mut boundary: HashMap<usize, Vec<usize>> = HashMap::new();
+        for h in topology.boundary_half_edges() {
+            let (u, v) = topology.half_edge_vertices(h);
+            boundary.entry(u).or_default().push(v);
+        }
+
+        // Walk the boundary edges, starting at the lowest vertex to get a stable result.
+        let mut starts = boundary.keys().copied().collect::<Vec<_>>();
+        starts.sort_unstable();
+        let mut loops = Vec::new();
+        for start in starts {
+            while boundary.get(&start).is_some_and(|next| !next.is_empty()) {
+                let mut cycle = vec![start];
+                let mut current = start;
+                // The walk ends early at a dangling boundary, which can only happen with
+                // non-manifold input.
+                while let Some(next) = boundary.get_mut(&current).and_then(|next| next.pop()) {
+                    if next == start {
+                        break;
+                    }
+                    // A vertex shared by two holes: split off the inner cycle.
+                    if let Some(pos) = cycle.iter().position(|&v| v == next) {
+                        loops.push(cycle.split_off(pos));
+                    }
+                    cycle.push(next);
+                    current = next;
+                }
+                loops.push(cycle);
+            }
+        }
+        loops
+    }
+
+    /// Closes every hole with at most `max_hole_size` boundary edges by triangulating it.
+    ///
+    /// Every hole is filled with the triangulation of minimal total area, which works well for
+    /// the mostly planar holes left by scanners and broken exports. The computation is cubic in
+    /// the size of the hole, which `max_hole_size` also keeps in check.
+    /// The new faces are wound consistently with their neighbors. Returns the number of holes
+    /// that have been filled.
+    pub fn fill_holes(&mut self, max_hole_size: usize) -> usize {
+        let mut filled = 0;
+        for mut hole in self.boundary_loops() {
+            if hole.len() < 3 || hole.len() > max_hole_size {
+                continue;
+            }
+            // The new faces have to traverse the boundary in the opposite direction.
+            hole.reverse();
+            for [a, b, c] in min_area_triangulation(&self.vertices, &hole) {
+                let normal =
+                    super::utils::normal(self.vertices[a], self.vertices[b], self.vertices[c]);
+                self.faces.push(IndexedTriangle {
+                    normal,
+                    vertices: [a, b, c],
+                });
+            }
+            filled += 1;
+        }
+        filled
+    }
+}
+
+// Triangulates a closed polygon of vertex indices, minimizing the sum of triangle areas.
+// The triangles keep the orientation of the polygon.
+fn min_area_triangulation(vertices: &[Vertex], polygon: &[usize]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    let area = |i: usize, k: usize, j: usize| {
+        super::utils::tri_area(
+            vertices[polygon[i]],
+            vertices[polygon[k]],
+            vertices[polygon[j]],
+        )
+    };
+    // cost[i][j] and split[i][j] describe the best triangulation of polygon[i..=j].
+    let mut cost = vec![vec![0.0f32; n]; n];
+    let mut split = vec![vec![0; n]; n];
+    for len in 2..n {
+        for i in 0..n - len {
+            let j = i + len;
+            cost[i][j] = f32::INFINITY;
+            for k in i + 1..j {
+                let c = cost[i][k] + cost[k][j] + area(i, k, j);
+                if c < cost[i][j] {
+                    cost[i][j] = c;
+                    split[i][j] = k;
+                }
+            }
+        }
+    }
+
+    let mut triangles = Vec::with_capacity(n - 2);
+    let mut ranges = vec![(0, n - 1)];
+    while let Some((i, j)) = ranges.pop() {
+        if j - i < 2 {
+            continue;
+        }
+        let k = split[i][j];
+        triangles.push([polygon[i], polygon[k], polygon[j]]);
+        ranges.push((i, k));
+        ranges.push((k, j));
+    }
+    triangles
+}
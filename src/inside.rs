@@ -0,0 +1,117 @@
+use crate::bvh::{Bvh, ClosestPoint};
+use crate::types::Vertex;
+use std::cell::Cell;
+
+// Nodes whose center is farther from the query than this many times the half diagonal of their
+// box are approximated in winding number computations.
+const FAR_AWAY: f64 = 3.0;
+
+// Distance to the surface, relative to the largest coordinate of the mesh, below which points
+// count as on the surface.
+const SURFACE_TOLERANCE: f32 = 16.0 * f32::EPSILON;
+
+impl Bvh {
+    /// Generalized winding number of the mesh around `point`.
+    ///
+    /// It is the sum of the solid angles of all faces as seen from the point, divided by 4π. For
+    /// a closed mesh with outward facing triangles (see
+    /// [validate](struct.IndexedMesh.html#method.validate)) it is 1 inside and 0 outside, nested
+    /// shells add up. Unlike casting rays, this does not depend on hitting edges or vertices
+    /// exactly, and it degrades gracefully for small holes in the mesh.
+    ///
+    /// Faces far from the point relative to the size of their node in the hierarchy are
+    /// approximated together, following Barill et al., Fast Winding Numbers for Soups and
+    /// Clouds. This makes a query take about logarithmic time, at an error of a few hundredths at
+    /// most, so compare against 0.5 rather than testing for whole numbers.
+    pub fn winding_number(&self, point: Vertex) -> f64 {
+        let p = point.0.map(f64::from);
+        let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+        let far_away = Cell::new(0.0);
+        let mut near = 0.0;
+        self.traverse(
+            &mut near,
+            |_, node| {
+                let center = node.center();
+                let r = [0, 1, 2].map(|i| center[i] - p[i]);
+                let half_diagonal =
+                    [0, 1, 2].map(|i| (f64::from(node.max[i]) - f64::from(node.min[i])) / 2.0);
+                let squared = dot(r, r);
+                if squared <= (FAR_AWAY * FAR_AWAY) * dot(half_diagonal, half_diagonal) {
+                    return true;
+                }
+                // Taylor expansion of the solid angles of the faces about the center of their
+                // box, up to the first moment of their area weighted normals.
+                let m = &node.moment;
+                let trace = m[0][0] + m[1][1] + m[2][2];
+                let rmr = dot(r, [0, 1, 2].map(|j| dot(m[j], r)));
+                let cubed = squared * squared.sqrt();
+                far_away.set(
+                    far_away.get() + dot(r, node.area_normal) / cubed + trace / cubed
+                        - 3.0 * rmr / (cubed * squared),
+                );
+                false
+            },
+            |near, fi| {
+                let [a, b, c] =
+                    self.triangles[fi].map(|v| [0, 1, 2].map(|i| f64::from(v[i]) - p[i]));
+                let length = |v: [f64; 3]| dot(v, v).sqrt();
+                let (la, lb, lc) = (length(a), length(b), length(c));
+                // Solid angle of the triangle, after Van Oosterom and Strackee.
+                let determinant = a[0] * (b[1] * c[2] - b[2] * c[1])
+                    - a[1] * (b[0] * c[2] - b[2] * c[0])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0]);
+                let denominator = la * lb * lc + dot(a, b) * lc + dot(b, c) * la + dot(c, a) * lb;
+                *near += 2.0 * determinant.atan2(denominator);
+            },
+        );
+        (near + far_away.get()) / (4.0 * ::std::f64::consts::PI)
+    }
+
+    /// Whether `point` lies inside the mesh, which should be closed with outward facing
+    /// triangles. Points on the surface, up to rounding errors, count as inside.
+    ///
+    /// ```
+    /// use stl_io::{Bvh, IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let tetrahedron = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 1.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// let bvh = Bvh::new(&tetrahedron);
+    /// assert!(bvh.contains(Vertex::new([0.1, 0.1, 0.1])));
+    /// assert!(bvh.contains(Vertex::new([0.0, 0.0, 0.0])));
+    /// assert!(!bvh.contains(Vertex::new([1.0, 1.0, 1.0])));
+    /// assert_eq!(bvh.signed_distance(Vertex::new([0.1, 0.2, -2.0])), 2.0);
+    /// ```
+    pub fn contains(&self, point: Vertex) -> bool {
+        match self.closest_point(point) {
+            Some(closest) => self.inside(point, &closest),
+            None => false,
+        }
+    }
+
+    // Whether a point lies inside, given its closest point on the surface. Points within rounding
+    // errors of the coordinates from the surface count as on it.
+    fn inside(&self, point: Vertex, closest: &ClosestPoint) -> bool {
+        let (min, max) = self.bounds().unwrap_or_default();
+        let scale = (0..3)
+            .map(|c| min[c].abs().max(max[c].abs()))
+            .fold(0.0, f32::max);
+        closest.distance <= SURFACE_TOLERANCE * scale || self.winding_number(point) >= 0.5
+    }
+
+    /// Distance of `point` to the surface of the mesh, negative inside of it, see
+    /// [contains](#method.contains). Returns infinity for meshes without faces.
+    pub fn signed_distance(&self, point: Vertex) -> f32 {
+        match self.closest_point(point) {
+            Some(closest) if self.inside(point, &closest) => -closest.distance,
+            Some(closest) => closest.distance,
+            None => f32::INFINITY,
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::bvh::Bvh;
+use crate::predicates::{orient2d, orient3d};
+use crate::types::{IndexedMesh, Vertex};
+
+type Point = [f64; 3];
+type Point2 = [f64; 2];
+
+fn sub(a: Point, b: Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn on_segment(a: Point2, b: Point2, p: Point2) -> bool {
+    orient2d(a, b, p) == 0.0
+        && (p[0] - a[0]) * (p[0] - b[0]) <= 0.0
+        && (p[1] - a[1]) * (p[1] - b[1]) <= 0.0
+}
+
+// Whether the closed segments ab and cd share a point.
+fn segments_touch(a: Point2, b: Point2, c: Point2, d: Point2) -> bool {
+    let (d1, d2) = (orient2d(c, d, a), orient2d(c, d, b));
+    let (d3, d4) = (orient2d(a, b, c), orient2d(a, b, d));
+    (d1 * d2 < 0.0 && d3 * d4 < 0.0)
+        || on_segment(c, d, a)
+        || on_segment(c, d, b)
+        || on_segment(a, b, c)
+        || on_segment(a, b, d)
+}
+
+// Whether p lies in the closed triangle.
+fn in_triangle(t: &[Point2; 3], p: Point2) -> bool {
+    let s = [0, 1, 2].map(|i| orient2d(t[i], t[(i + 1) % 3], p));
+    s.iter().all(|&x| x >= 0.0) || s.iter().all(|&x| x <= 0.0)
+}
+
+// Projects points onto the coordinate plane most parallel to the given normal.
+fn project<const N: usize>(normal: Point, points: [Point; N]) -> [Point2; N] {
+    let drop = (0..3)
+        .max_by(|&a, &b| normal[a].abs().total_cmp(&normal[b].abs()))
+        .unwrap();
+    let (x, y) = ((drop + 1) % 3, (drop + 2) % 3);
+    points.map(|p| [p[x], p[y]])
+}
+
+// Whether the corners of a triangle are collinear, which holds if they are in every projection.
+fn degenerate(t: &[Point; 3]) -> bool {
+    (0..3).all(|drop| {
+        let [a, b, c] = t.map(|p| [p[(drop + 1) % 3], p[(drop + 2) % 3]]);
+        orient2d(a, b, c) == 0.0
+    })
+}
+
+fn normal(t: &[Point; 3]) -> Point {
+    let (u, v) = (sub(t[1], t[0]), sub(t[2], t[0]));
+    [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ]
+}
+
+// Whether the closed segment pq shares a point with the closed triangle t.
+fn segment_touches_triangle(p: Point, q: Point, t: &[Point; 3]) -> bool {
+    let (sp, sq) = (orient3d(t[0], t[1], t[2], p), orient3d(t[0], t[1], t[2], q));
+    if (sp > 0.0 && sq > 0.0) || (sp < 0.0 && sq < 0.0) {
+        return false;
+    }
+    if sp == 0.0 && sq == 0.0 {
+        let [a, b, c, p, q] = project(normal(t), [t[0], t[1], t[2], p, q]);
+        let triangle = [a, b, c];
+        return in_triangle(&triangle, p)
+            || in_triangle(&triangle, q)
+            || (0..3).any(|i| segments_touch(p, q, triangle[i], triangle[(i + 1) % 3]));
+    }
+    let s = [0, 1, 2].map(|i| orient3d(p, q, t[i], t[(i + 1) % 3]));
+    s.iter().all(|&x| x >= 0.0) || s.iter().all(|&x| x <= 0.0)
+}
+
+// Whether two coplanar triangles without common vertices share a point.
+fn coplanar_triangles_touch(t: &[Point; 3], u: &[Point; 3]) -> bool {
+    let [a, b, c, d, e, f] = project(normal(t), [t[0], t[1], t[2], u[0], u[1], u[2]]);
+    let (t, u) = ([a, b, c], [d, e, f]);
+    t.iter().any(|&p| in_triangle(&u, p))
+        || u.iter().any(|&p| in_triangle(&t, p))
+        || (0..3)
+            .any(|i| (0..3).any(|j| segments_touch(t[i], t[(i + 1) % 3], u[j], u[(j + 1) % 3])))
+}
+
+// Whether the open angular sectors from v spanned by (a, b) and by (c, d) overlap, for
+// coplanar triangles vab and vcd. Each triangle covers its sector near v, so this is exactly
+// when the triangles overlap.
+fn sectors_overlap(v: Point2, a: Point2, b: Point2, c: Point2, d: Point2) -> bool {
+    let direction = |p: Point2| {
+        let (x, y) = (p[0] - v[0], p[1] - v[1]);
+        let l = (x * x + y * y).sqrt();
+        [x / l, y / l]
+    };
+    let origin = [0.0, 0.0];
+    let inside = |from: Point2, to: Point2, u: Point2| {
+        let s = orient2d(origin, from, to).signum();
+        s * orient2d(origin, from, u) > 0.0 && s * orient2d(origin, u, to) > 0.0
+    };
+    let rays = [a, b, c, d].map(direction);
+    // The overlap is itself a sector bounded by two of the rays, whose bisector lies inside.
+    (0..4).any(|i| {
+        (i + 1..4).any(|j| {
+            let u = [rays[i][0] + rays[j][0], rays[i][1] + rays[j][1]];
+            inside(rays[0], rays[1], u) && inside(rays[2], rays[3], u)
+        })
+    })
+}
+
+// Whether two faces of a mesh intersect anywhere but in their common vertices and edge.
+fn faces_intersect(fi: [usize; 3], t: &[Point; 3], fj: [usize; 3], u: &[Point; 3]) -> bool {
+    let shared = (0..3)
+        .filter_map(|i| (0..3).find(|&j| fi[i] == fj[j]).map(|j| (i, j)))
+        .collect::<Vec<_>>();
+    let coplanar = u.iter().all(|&p| orient3d(t[0], t[1], t[2], p) == 0.0);
+    match shared.len() {
+        0 => {
+            if coplanar {
+                coplanar_triangles_touch(t, u)
+            } else {
+                (0..3).any(|i| segment_touches_triangle(t[i], t[(i + 1) % 3], u))
+                    || (0..3).any(|i| segment_touches_triangle(u[i], u[(i + 1) % 3], t))
+            }
+        }
+        1 => {
+            let (i, j) = shared[0];
+            let (a, b) = (t[(i + 1) % 3], t[(i + 2) % 3]);
+            let (c, d) = (u[(j + 1) % 3], u[(j + 2) % 3]);
+            if coplanar {
+                let [v, a, b, c, d] = project(normal(t), [t[i], a, b, c, d]);
+                sectors_overlap(v, a, b, c, d)
+            } else {
+                // Away from the common vertex, one triangle has to pass through the edge of the
+                // other opposite of it.
+                segment_touches_triangle(a, b, u) || segment_touches_triangle(c, d, t)
+            }
+        }
+        2 => {
+            // Triangles sharing an edge only overlap if one is folded onto the other.
+            let i = (0..3).find(|&i| shared.iter().all(|s| s.0 != i)).unwrap();
+            let j = (0..3).find(|&j| shared.iter().all(|s| s.1 != j)).unwrap();
+            let (a, b) = (t[shared[0].0], t[shared[1].0]);
+            let [a, b, p, q] = project(normal(t), [a, b, t[i], u[j]]);
+            coplanar && orient2d(a, b, p) * orient2d(a, b, q) > 0.0
+        }
+        // Duplicate faces cover each other completely.
+        _ => true,
+    }
+}
+
+impl IndexedMesh {
+    /// Finds all pairs of faces that intersect each other, as `(i, j)` with `i < j`, sorted.
+    ///
+    /// Faces touching only in common vertices, or along a common edge, as neighbouring faces
+    /// do, do not count. Any other contact does, including faces merely touching each other
+    /// and duplicate faces.
+    /// Vertices are compared by index, so duplicate vertices should be merged first, as
+    /// [read_stl](fn.read_stl.html) does. Degenerate faces are skipped, see
+    /// [remove_degenerate_faces](#method.remove_degenerate_faces).
+    /// Candidates are found with a [Bvh](struct.Bvh.html), and the geometric tests use
+    /// orientation predicates with exact signs, so touching and coplanar faces are detected
+    /// reliably.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let mut tetrahedron = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 1.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// assert!(tetrahedron.self_intersections().is_empty());
+    /// // Add a second, shifted copy piercing the first one.
+    /// let shifted = tetrahedron.vertices.iter().map(|v| Vertex::new([v[0], v[1], v[2] - 0.5]));
+    /// tetrahedron.vertices.extend(shifted.collect::<Vec<_>>());
+    /// tetrahedron.faces.push(face([5, 6, 7]));
+    /// assert_eq!(tetrahedron.self_intersections(), vec![(0, 4), (1, 4), (3, 4)]);
+    /// ```
+    pub fn self_intersections(&self) -> Vec<(usize, usize)> {
+        let bvh = Bvh::new(self);
+        let triangles = bvh
+            .triangles
+            .iter()
+            .map(|t| t.map(|v| v.0.map(f64::from)))
+            .collect::<Vec<_>>();
+        let degenerate = triangles.iter().map(degenerate).collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+        for (i, triangle) in bvh.triangles.iter().enumerate() {
+            if degenerate[i] {
+                continue;
+            }
+            let corner = |pick: fn(f32, f32) -> f32| {
+                Vertex::new(
+                    [0, 1, 2].map(|c| pick(pick(triangle[0][c], triangle[1][c]), triangle[2][c])),
+                )
+            };
+            for j in bvh.faces_in_box(corner(f32::min), corner(f32::max)) {
+                if j > i
+                    && !degenerate[j]
+                    && faces_intersect(
+                        self.faces[i].vertices,
+                        &triangles[i],
+                        self.faces[j].vertices,
+                        &triangles[j],
+                    )
+                {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+}
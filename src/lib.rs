@@ -31,20 +31,90 @@
 //! let mut file = OpenOptions::new().write(true).create_new(true).open("mesh.stl").unwrap();
 //! stl_io::write_stl(&mut file, mesh.iter()).unwrap();
 //! ```
+//!
+//! Besides STL, meshes can be read from and written to OBJ, PLY, OFF and AMF, and exported to
+//! glTF for web viewers. [load_mesh](fn.load_mesh.html) and [save_mesh](fn.save_mesh.html) pick
+//! the format by file extension and content.
+//!
+//! # Optional features
+//!
+//! * `threemf`: reading and writing of [3MF](https://3mf.io/) files, see
+//!   [read_3mf](fn.read_3mf.html). Enables `zip`.
+//! * `gzip`, `zstd`: transparent reading of compressed STL files, e.g. `.stl.gz` or `.stl.zst`,
+//!   and writing them with [write_stl_gzip](fn.write_stl_gzip.html) and
+//!   [write_stl_zstd](fn.write_stl_zstd.html).
+//! * `zip`: reading and writing of compressed AMF files, and of zip archives holding STL files,
+//!   see [read_stl_zip](fn.read_stl_zip.html).
 
 #![warn(missing_docs)]
 
+mod amf;
 mod ascii_reader;
 mod binary_reader;
+mod bvh;
+mod cleanup;
+mod components;
+mod compression;
+mod cut;
+mod extrude;
+mod formats;
+mod gltf;
+mod heightmap;
+mod holes;
+mod inside;
+mod intersections;
+mod marching;
+mod obj;
+mod off;
+mod orient;
+mod ply;
+mod predicates;
+mod primitives;
+mod slice;
+mod svg;
+#[cfg(feature = "threemf")]
+mod threemf;
+mod topology;
+mod transform;
+mod triangulate;
 mod types;
+mod units;
 mod utils;
+mod voxel;
 mod writer;
+mod xml;
 
 use std::io::Result;
 use std::iter::Iterator;
 
+#[cfg(feature = "zip")]
+pub use amf::write_amf_zip;
+pub use amf::{read_amf, write_amf, AmfMaterial, AmfModel, AmfObject, AmfVolume};
+pub use bvh::{Bvh, ClosestPoint, Ray, RayHit};
+pub use cleanup::CleanupReport;
+#[cfg(feature = "gzip")]
+pub use compression::write_stl_gzip;
+#[cfg(feature = "zstd")]
+pub use compression::write_stl_zstd;
+#[cfg(feature = "zip")]
+pub use compression::{read_stl_zip, write_stl_zip, StlZipEntries};
+pub use formats::{load_mesh, save_mesh, FormatRegistry, MeshFormat};
+pub use gltf::{write_glb, write_gltf};
+pub use marching::ScalarGrid;
+pub use obj::{read_obj, read_obj_groups, write_obj, write_obj_groups, ObjGroup};
+pub use off::{read_off, read_off_with_colors, write_off, write_off_with_colors, OffColors};
+pub use orient::OrientationReport;
+pub use ply::{read_ply, read_ply_with_colors, write_ply, write_ply_with_colors, PlyFormat};
+pub use slice::{Contour, Layer, Plane};
+pub use svg::{write_svg_layer, write_svg_layers};
+#[cfg(feature = "threemf")]
+pub use threemf::{read_3mf, write_3mf, ThreeMfItem, ThreeMfModel};
+pub use topology::Topology;
+pub use transform::{Transform, Transformed};
 pub use types::{IndexedMesh, IndexedTriangle, Normal, Triangle, Vector, Vertex};
-pub use writer::write_stl;
+pub use units::Units;
+pub use voxel::{SparseVoxelGrid, VoxelFill, VoxelGrid};
+pub use writer::{write_stl, write_stl_with_header};
 
 /// Iterates over all Triangles in a STL.
 pub trait TriangleIterator: ::std::iter::Iterator<Item = Result<Triangle>> {
@@ -97,8 +167,38 @@ pub trait TriangleIterator: ::std::iter::Iterator<Item = Result<Triangle>> {
             faces: triangles,
         })
     }
+
+    /// Lazily applies a [Transform](struct.Transform.html) to every Triangle, see
+    /// [Triangle::transform](struct.Triangle.html#method.transform).
+    ///
+    /// ```
+    /// let mut reader = ::std::io::Cursor::new(b"solid foobar
+    /// facet normal 0 0 1
+    ///     outer loop
+    ///         vertex 0 0 0
+    ///         vertex 1 0 0
+    ///         vertex 0 1 0
+    ///     endloop
+    /// endfacet
+    /// endsolid foobar".to_vec());
+    /// use stl_io::TriangleIterator;
+    /// let stl = stl_io::create_stl_reader(&mut reader).unwrap();
+    /// let moved = stl
+    ///     .transformed(stl_io::Transform::translation([0.0, 0.0, 5.0]))
+    ///     .as_indexed_triangles()
+    ///     .unwrap();
+    /// assert_eq!(moved.vertices[0], stl_io::Vertex::new([0.0, 0.0, 5.0]));
+    /// ```
+    fn transformed(self, transform: Transform) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed::new(self, transform)
+    }
 }
 
+impl<T: TriangleIterator + ?Sized> TriangleIterator for Box<T> {}
+
 /// Attempts to read either ascii or binary STL from std::io::Read.
 ///
 /// ```
@@ -121,9 +221,52 @@ where
     create_stl_reader(read)?.as_indexed_triangles()
 }
 
+/// Reads the 80 byte header of a binary STL or the solid name of an ascii STL, without the
+/// trailing zeros or whitespace, and seeks back to the start, so that `read` can be passed on to
+/// [read_stl](fn.read_stl.html). Non UTF-8 bytes are replaced.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"solid bracket UNITS=in
+/// endsolid".to_vec());
+/// assert_eq!(stl_io::read_stl_header(&mut reader).unwrap(), "bracket UNITS=in");
+/// ```
+pub fn read_stl_header<R>(read: &mut R) -> Result<String>
+where
+    R: ::std::io::Read + ::std::io::Seek,
+{
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    if let Some(compression) = compression::detect_compression(read)? {
+        let header = {
+            let mut decompressed = compression::decompressing_reader(read, compression)?;
+            compression::stream_stl_header(&mut decompressed)?
+        };
+        read.seek(::std::io::SeekFrom::Start(0))?;
+        return Ok(header);
+    }
+    let header = match ascii_reader::AsciiStlReader::probe(read) {
+        Ok(()) => {
+            let mut line = String::new();
+            ::std::io::BufRead::read_line(&mut ::std::io::BufReader::new(&mut *read), &mut line)?;
+            line["solid ".len()..].trim().to_string()
+        }
+        Err(_) => {
+            let mut header = [0u8; 80];
+            read.read_exact(&mut header)?;
+            String::from_utf8_lossy(&header)
+                .trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+                .to_string()
+        }
+    };
+    read.seek(::std::io::SeekFrom::Start(0))?;
+    Ok(header)
+}
+
 /// Attempts to create a [TriangleIterator](trait.TriangleIterator.html) for either ascii or binary
 /// STL from std::io::Read.
 ///
+/// With the `gzip` or `zstd` feature, compressed STL files are recognized by their signature and
+/// decompressed while reading. Without the feature, they are read like any other binary STL.
+///
 /// ```
 /// let mut reader = ::std::io::Cursor::new(b"solid foobar
 /// facet normal 1 2 3
@@ -142,6 +285,11 @@ pub fn create_stl_reader<'a, R>(
 where
     R: ::std::io::Read + ::std::io::Seek,
 {
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    if let Some(compression) = compression::detect_compression(read)? {
+        let decompressed = compression::decompressing_reader(read, compression)?;
+        return compression::stream_stl_reader(decompressed);
+    }
     match ascii_reader::AsciiStlReader::probe(read) {
         Ok(()) => ascii_reader::AsciiStlReader::create_triangle_iterator(read),
         Err(_) => binary_reader::BinaryStlReader::create_triangle_iterator(read),
@@ -173,6 +321,33 @@ mod test {
         mesh
     }
 
+    // Builds a mesh from raw coordinates and vertex indices, normals are left zero.
+    fn mesh_from(vertices: &[[f32; 3]], faces: &[[usize; 3]]) -> super::IndexedMesh {
+        super::IndexedMesh {
+            vertices: vertices.iter().map(|v| Vertex::new(*v)).collect(),
+            faces: faces
+                .iter()
+                .map(|f| IndexedTriangle {
+                    normal: Normal::default(),
+                    vertices: *f,
+                })
+                .collect(),
+        }
+    }
+
+    // An axis aligned box with outward facing triangles.
+    fn cuboid(min: [f32; 3], max: [f32; 3]) -> super::IndexedMesh {
+        super::IndexedMesh::cuboid(Vertex::new(min), Vertex::new(max))
+    }
+
+    // A closed tetrahedron with outward facing triangles.
+    fn tetrahedron() -> super::IndexedMesh {
+        mesh_from(
+            &[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]],
+            &[[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]],
+        )
+    }
+
     #[test]
     fn read_ascii_stl_simple_success() {
         let mut reader = ::std::io::Cursor::new(
@@ -445,7 +620,7 @@ mod test {
             let a = stl.vertices[face.vertices[0]];
             let b = stl.vertices[face.vertices[1]];
             let c = stl.vertices[face.vertices[2]];
-            total_area = total_area + utils::tri_area(a, b, c);
+            total_area += utils::tri_area(a, b, c);
         }
 
         // area of bunny model according to blender
@@ -471,4 +646,2042 @@ mod test {
 
         assert_eq!(stl.into_triangle_vec(), triangle_reference_vector);
     }
+
+    #[test]
+    fn cleanup_removes_degenerate_and_duplicate_faces() {
+        let mut mesh = tetrahedron();
+        mesh.vertices.push(Vertex::new([2., 2., 2.]));
+        mesh.vertices.push(Vertex::new([0.5, 0., 0.]));
+        // collapsed, zero-area, duplicate and duplicate with opposite winding.
+        mesh.faces.push(mesh.faces[0]);
+        mesh.faces[4].vertices = [0, 0, 1];
+        mesh.faces.push(mesh.faces[0]);
+        mesh.faces[5].vertices = [0, 5, 1];
+        mesh.faces.push(mesh.faces[1]);
+        mesh.faces.push(mesh.faces[2]);
+        mesh.faces[7].vertices = [3, 2, 1];
+        assert!(mesh.validate().is_err());
+
+        let report = mesh.cleanup();
+        assert_eq!(
+            report,
+            CleanupReport {
+                degenerate_faces: 2,
+                duplicate_faces: 2,
+                unreferenced_vertices: 2,
+            }
+        );
+        assert_eq!(mesh, tetrahedron());
+        assert!(mesh.validate().is_ok());
+    }
+
+    #[test]
+    fn remove_unreferenced_vertices_compacts_indices() {
+        let mut mesh = mesh_from(
+            &[
+                [9., 9., 9.],
+                [0., 0., 0.],
+                [8., 8., 8.],
+                [1., 0., 0.],
+                [0., 1., 0.],
+            ],
+            &[[1, 3, 4]],
+        );
+        assert_eq!(mesh.remove_unreferenced_vertices(), 2);
+        assert_eq!(
+            mesh,
+            mesh_from(&[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]], &[[0, 1, 2]])
+        );
+    }
+
+    #[test]
+    fn orient_faces_fixes_winding_and_inside_out_meshes() {
+        let mut mesh = tetrahedron();
+        orient::flip_face(&mut mesh.faces[2]);
+        assert!(mesh.validate().is_err());
+        let report = mesh.orient_faces();
+        assert_eq!(report.flipped_faces, 1);
+        assert_eq!(report.components, 1);
+        assert!(report.non_orientable_components.is_empty());
+        assert_eq!(mesh, tetrahedron());
+
+        // A consistently wound but inside-out mesh is flipped entirely.
+        for face in &mut mesh.faces {
+            orient::flip_face(face);
+        }
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.orient_faces().flipped_faces, 4);
+        assert_eq!(mesh, tetrahedron());
+    }
+
+    #[test]
+    fn orient_faces_reports_non_orientable_components() {
+        // A Möbius strip made of three quads, next to a correctly oriented tetrahedron.
+        let mut mesh = mesh_from(
+            &[
+                [1., 0., 0.],
+                [1.5, 0., 0.],
+                [-0.5, 0.9, 0.1],
+                [-0.7, 1.2, -0.1],
+                [-0.5, -0.9, -0.1],
+                [-0.7, -1.2, 0.1],
+            ],
+            &[
+                [0, 1, 3],
+                [0, 3, 2],
+                [2, 3, 5],
+                [2, 5, 4],
+                [4, 5, 0],
+                [4, 0, 1],
+            ],
+        );
+        let mut tetrahedron = tetrahedron();
+        for face in &mut tetrahedron.faces {
+            face.vertices = face.vertices.map(|v| v + 6);
+        }
+        mesh.vertices.extend(tetrahedron.vertices);
+        mesh.faces.extend(tetrahedron.faces);
+
+        let report = mesh.orient_faces();
+        assert_eq!(report.components, 2);
+        assert_eq!(
+            report.non_orientable_components,
+            vec![vec![0, 1, 2, 3, 4, 5]]
+        );
+    }
+
+    #[test]
+    fn boundary_loops_of_open_box() {
+        // Unit cube without its top, the hole is the square at z = 1.
+        let mut mesh = mesh_from(
+            &[
+                [0., 0., 0.],
+                [1., 0., 0.],
+                [1., 1., 0.],
+                [0., 1., 0.],
+                [0., 0., 1.],
+                [1., 0., 1.],
+                [1., 1., 1.],
+                [0., 1., 1.],
+            ],
+            &[
+                [0, 2, 1],
+                [0, 3, 2],
+                [0, 1, 5],
+                [0, 5, 4],
+                [1, 2, 6],
+                [1, 6, 5],
+                [2, 3, 7],
+                [2, 7, 6],
+                [3, 0, 4],
+                [3, 4, 7],
+            ],
+        );
+        assert_eq!(mesh.boundary_loops(), vec![vec![4, 7, 6, 5]]);
+
+        assert_eq!(mesh.fill_holes(3), 0);
+        assert_eq!(mesh.fill_holes(4), 1);
+        assert_eq!(mesh.faces.len(), 12);
+        assert!(mesh.boundary_loops().is_empty());
+        assert!(mesh.validate().is_ok());
+        for face in &mesh.faces[10..] {
+            assert_eq!(face.normal, Normal::new([0., 0., 1.]));
+        }
+    }
+
+    #[test]
+    fn fill_holes_bunny() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let mut stl = read_stl(&mut reader).unwrap();
+        let holes = stl.boundary_loops();
+        assert!(!holes.is_empty());
+        let longest = holes.iter().map(|h| h.len()).max().unwrap();
+        let area = |m: &IndexedMesh| {
+            m.faces
+                .iter()
+                .map(|f| {
+                    let v = f.vertices.map(|i| m.vertices[i]);
+                    utils::tri_area(v[0], v[1], v[2])
+                })
+                .sum::<f32>()
+        };
+        let original_area = area(&stl);
+        assert_eq!(stl.fill_holes(longest), holes.len());
+        assert!(stl.boundary_loops().is_empty());
+        assert!(area(&stl) > original_area);
+    }
+
+    #[test]
+    fn split_and_merge_components() {
+        let mut shifted = tetrahedron();
+        for v in &mut shifted.vertices {
+            v.0[0] += 1.0;
+        }
+        let mut mesh = IndexedMesh::merge(&[tetrahedron(), shifted.clone()], None);
+        assert_eq!(mesh.vertices.len(), 8);
+        mesh.vertices.push(Vertex::new([7., 7., 7.]));
+
+        let components = mesh.connected_components();
+        assert_eq!(components, vec![tetrahedron(), shifted]);
+        for component in &components {
+            assert!(component.validate().is_ok());
+        }
+
+        // Welding reconnects the shared corner (1, 0, 0) of both tetrahedra.
+        let welded = IndexedMesh::merge(&components, Some(1e-6));
+        assert_eq!(welded.vertices.len(), 7);
+        assert_eq!(welded.connected_components().len(), 1);
+
+        let mut binary_stl = Vec::<u8>::new();
+        write_stl(&mut binary_stl, welded.clone().into_triangle_vec().iter()).unwrap();
+        let reread = read_stl(&mut ::std::io::Cursor::new(binary_stl)).unwrap();
+        assert_eq!(reread.faces.len(), welded.faces.len());
+    }
+
+    #[test]
+    fn topology_of_tetrahedron() {
+        let mesh = tetrahedron();
+        let topology = mesh.topology();
+        assert_eq!(topology.num_half_edges(), 12);
+        assert_eq!(topology.boundary_half_edges().count(), 0);
+        assert_eq!(topology.edges().count(), 6);
+        for (u, v) in topology.edges() {
+            assert_eq!(topology.edge_valence(u, v), 2);
+        }
+        for h in 0..topology.num_half_edges() {
+            let t = topology.twin(h).unwrap();
+            assert_eq!(topology.twin(t), Some(h));
+            let (u, v) = topology.half_edge_vertices(h);
+            assert_eq!(topology.half_edge_vertices(t), (v, u));
+            assert_eq!(topology.next(topology.prev(h)), h);
+        }
+        assert_eq!(topology.face_neighbors(0), [Some(3), Some(2), Some(1)]);
+        for v in 0..4 {
+            assert_eq!(topology.vertex_faces(v).len(), 3);
+            assert!(!topology.is_boundary_vertex(v));
+            let mut ring = topology.one_ring(v);
+            assert_eq!(ring.len(), 3);
+            ring.sort_unstable();
+            assert_eq!(ring, (0..4).filter(|&w| w != v).collect::<Vec<_>>());
+        }
+        // Walking around vertex 0 follows the winding: 0 -> 2 -> 1 in face [0, 2, 1] is
+        // followed by face [0, 1, 3].
+        assert_eq!(topology.one_ring(0), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn topology_non_manifold_edge() {
+        // Three triangles sharing the edge 0 - 1.
+        let mesh = mesh_from(
+            &[
+                [0., 0., 0.],
+                [1., 0., 0.],
+                [0., 1., 0.],
+                [0., -1., 0.],
+                [0., 0., 1.],
+            ],
+            &[[0, 1, 2], [1, 0, 3], [1, 0, 4]],
+        );
+        let topology = mesh.topology();
+        assert_eq!(topology.edge_valence(0, 1), 3);
+        assert_eq!(topology.face_neighbors(0)[0], Some(1));
+        assert_eq!(topology.face_neighbors(2)[0], None);
+        assert!(mesh.validate().is_err());
+    }
+
+    #[test]
+    fn transform_rotations_agree() {
+        use float_cmp::ApproxEq;
+        let angle = ::std::f32::consts::FRAC_PI_2;
+        let axis_angle = Transform::rotation_axis_angle([0., 0., 2.], angle);
+        let quaternion =
+            Transform::rotation_quaternion([0., 0., (angle / 2.).sin(), (angle / 2.).cos()]);
+        let euler = Transform::rotation_euler([0., 0., angle]);
+        let p = Vertex::new([1., 2., 3.]);
+        let expected = Vertex::new([-2., 1., 3.]);
+        for t in [axis_angle, quaternion, euler] {
+            assert!(t.transform_point(p).approx_eq(&expected, (1e-6, 4)));
+            assert!(!t.is_mirroring());
+        }
+        // Euler angles are applied around x first, then y and z.
+        let euler = Transform::rotation_euler([angle, angle, 0.]);
+        assert!(euler
+            .transform_point(Vertex::new([0., 1., 0.]))
+            .approx_eq(&Vertex::new([1., 0., 0.]), (1e-6, 4)));
+    }
+
+    #[test]
+    fn transform_normals_with_inverse_transpose() {
+        // Non-uniform scaling of a slanted triangle: its normal must stay perpendicular.
+        let t = Transform::scale([1., 4., 1.]);
+        let mut triangle = Triangle {
+            normal: Normal::new([0.5f32.sqrt(), 0.5f32.sqrt(), 0.]),
+            vertices: [
+                Vertex::new([1., 0., 0.]),
+                Vertex::new([0., 1., 0.]),
+                Vertex::new([0., 1., 1.]),
+            ],
+        };
+        triangle.transform(&t);
+        let [a, b, c] = triangle.vertices;
+        let expected = utils::normal(a, b, c);
+        use float_cmp::ApproxEq;
+        assert!(triangle.normal.approx_eq(&expected, F32Margin::default()));
+    }
+
+    #[test]
+    fn mirroring_transform_keeps_mesh_valid() {
+        let mut mesh = tetrahedron();
+        for face in &mut mesh.faces {
+            let [a, b, c] = face.vertices.map(|i| mesh.vertices[i]);
+            face.normal = utils::normal(a, b, c);
+        }
+        let original = mesh.clone();
+        let t = Transform::mirror([1., 1., 0.]).then(&Transform::translation([1., 2., 3.]));
+        assert!(t.is_mirroring());
+        mesh.transform(&t);
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.orient_faces().flipped_faces, 0);
+        for face in &mesh.faces {
+            let [a, b, c] = face.vertices.map(|i| mesh.vertices[i]);
+            use float_cmp::ApproxEq;
+            assert!(face.normal.approx_eq(&utils::normal(a, b, c), (1e-6, 4)));
+        }
+
+        // The lazy adaptor produces the same mesh.
+        let mut stl = Vec::new();
+        write_stl(&mut stl, original.into_triangle_vec().iter()).unwrap();
+        let mut reader = ::std::io::Cursor::new(stl);
+        let transformed = create_stl_reader(&mut reader)
+            .unwrap()
+            .transformed(t)
+            .as_indexed_triangles()
+            .unwrap();
+        assert_eq!(transformed, mesh);
+    }
+
+    #[test]
+    fn units_from_header_text() {
+        assert_eq!(Units::from_text("UNITS=in"), Some(Units::Inches));
+        assert_eq!(Units::from_text("units = MM"), Some(Units::Millimeters));
+        assert_eq!(Units::from_text("unit:feet, scale 1"), Some(Units::Feet));
+        assert_eq!(
+            Units::from_text("community unit cm"),
+            Some(Units::Centimeters)
+        );
+        assert_eq!(Units::from_text("UNITS=parsec"), None);
+        for units in [
+            Units::Micrometers,
+            Units::Millimeters,
+            Units::Centimeters,
+            Units::Meters,
+            Units::Inches,
+            Units::Feet,
+        ] {
+            assert_eq!(Units::from_text(&units.header_text()), Some(units));
+        }
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        assert_eq!(read_stl_header(&mut reader).unwrap(), "");
+        assert_eq!(read_stl(&mut reader).unwrap().faces.len(), 99);
+    }
+
+    #[test]
+    fn write_stl_with_header_round_trip() {
+        let triangles = IndexedMesh::cube(1.0).into_triangle_vec();
+        let mut stl = Vec::new();
+        write_stl_with_header(&mut stl, b"solidworks export", triangles.iter()).unwrap();
+        let mut reader = ::std::io::Cursor::new(stl);
+        assert_eq!(read_stl_header(&mut reader).unwrap(), "solidworks export");
+        assert_eq!(read_stl(&mut reader).unwrap().faces.len(), 12);
+
+        // Readers would take this for ascii STL.
+        let mut stl = Vec::new();
+        let err = write_stl_with_header(&mut stl, b"solid part", triangles.iter()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = write_stl_with_header(&mut stl, &[0x1f, 0x8b, 8], triangles.iter()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = write_stl_with_header(&mut stl, &[b' '; 81], triangles.iter()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn units_guess_from_bounds() {
+        let mut cube = mesh_from(&[[0., 0., 0.], [20., 10., 5.]], &[]);
+        assert_eq!(Units::guess_from_bounds(&cube), Some(Units::Millimeters));
+        // 2 x 1 x 0.5 is too small for millimeters.
+        cube.convert_units(Units::Millimeters, Units::Centimeters);
+        assert_eq!(cube.bounding_box().unwrap().1, Vertex::new([2., 1., 0.5]));
+        assert_eq!(Units::guess_from_bounds(&cube), Some(Units::Inches));
+        // 5.25 x 1.5 x 0.75 could be millimeters, but are round inches.
+        let cube = mesh_from(&[[0., 0., 0.], [5.25, 1.5, 0.75]], &[]);
+        assert_eq!(Units::guess_from_bounds(&cube), Some(Units::Inches));
+        let cube = mesh_from(&[[0., 0., 0.], [5.3, 1.5, 0.75]], &[]);
+        assert_eq!(Units::guess_from_bounds(&cube), Some(Units::Millimeters));
+        // The bunny is modelled in meters.
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let bunny = read_stl(&mut reader).unwrap();
+        assert_eq!(Units::guess_from_bounds(&bunny), Some(Units::Meters));
+        assert_eq!(Units::guess_from_bounds(&mesh_from(&[], &[])), None);
+    }
+
+    #[test]
+    fn slice_nested_boxes() {
+        // A hollow box: the inner box is inverted to form the cavity.
+        let mut inner = cuboid([1., 1., 1.], [3., 3., 3.]);
+        for face in &mut inner.faces {
+            face.vertices.swap(1, 2);
+        }
+        let hollow = IndexedMesh::merge(&[cuboid([0., 0., 0.], [4., 4., 4.]), inner], None);
+        assert!(hollow.validate().is_ok());
+
+        let contours = hollow.slice(&Plane::horizontal(2.));
+        assert_eq!(contours.len(), 2);
+        let outer = contours.iter().position(|c| !c.is_hole).unwrap();
+        let hole = 1 - outer;
+        assert!(contours[outer].closed && contours[hole].closed);
+        assert_eq!(contours[outer].points.len(), 8);
+        assert_eq!(contours[outer].parent, None);
+        assert_eq!(contours[hole].parent, Some(outer));
+        assert_eq!(
+            utils::polygon_area(
+                &contours[outer]
+                    .points
+                    .iter()
+                    .map(|p| [p[0], p[1]])
+                    .collect::<Vec<_>>()
+            ),
+            16.
+        );
+
+        // Planes through vertices: the top of the cavity, the top and the bottom of the box.
+        assert_eq!(hollow.slice(&Plane::horizontal(1.)).len(), 1);
+        assert_eq!(hollow.slice(&Plane::horizontal(3.)).len(), 2);
+        let top = hollow.slice(&Plane::horizontal(4.));
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].points.len(), 4);
+        assert!(hollow.slice(&Plane::horizontal(0.)).is_empty());
+
+        let layers = hollow.slice_layers(0.5).unwrap();
+        assert_eq!(layers.len(), 8);
+        assert_eq!(layers[0].z, 0.25);
+        let counts = layers.iter().map(|l| l.contours.len()).collect::<Vec<_>>();
+        assert_eq!(counts, vec![1, 1, 2, 2, 2, 2, 1, 1]);
+        for layer_height in [0., -0.5, f32::NAN, f32::INFINITY, 1e-30] {
+            let error = hollow.slice_layers(layer_height).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn slice_along_arbitrary_plane() {
+        let cube = cuboid([-1., -1., -1.], [1., 1., 1.]);
+        let plane = Plane::new(Normal::new([1., 1., 1.]), Vertex::new([0., 0., 0.]));
+        let contours = cube.slice(&plane);
+        // The diagonal cross section of a cube is a regular hexagon, the face diagonals add
+        // another point on every side.
+        assert_eq!(contours.len(), 1);
+        assert_eq!(contours[0].points.len(), 12);
+        assert!(!contours[0].is_hole);
+        let projected = contours[0]
+            .points
+            .iter()
+            .map(|&p| plane.project(p))
+            .collect::<Vec<_>>();
+        assert!((utils::polygon_area(&projected) - 3. * 3f64.sqrt()).abs() < 1e-5);
+        for p in &contours[0].points {
+            assert!(plane.signed_distance(*p).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn slice_bunny_is_closed() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let mut bunny = read_stl(&mut reader).unwrap();
+        bunny.fill_holes(100);
+        let (min, max) = bunny.bounding_box().unwrap();
+        for layer in bunny.slice_layers((max[2] - min[2]) / 10.).unwrap() {
+            assert!(!layer.contours.is_empty());
+            assert!(layer.contours.iter().all(|c| c.closed));
+        }
+    }
+
+    #[test]
+    fn write_svg_layers_with_holes() {
+        let mut inner = cuboid([1., 1., 1.], [3., 3., 3.]);
+        for face in &mut inner.faces {
+            face.vertices.swap(1, 2);
+        }
+        let hollow = IndexedMesh::merge(&[cuboid([0., 0., 0.], [4., 4., 4.]), inner], None);
+        let bounds = hollow.bounding_box().unwrap();
+        let layers = hollow.slice_at_heights(&[0.5, 2.]);
+
+        let mut svg = Vec::<u8>::new();
+        write_svg_layers(&mut svg, &layers, bounds).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("width=\"4mm\" height=\"4mm\" viewBox=\"0 -4 4 4\""));
+        assert!(svg.contains("<g id=\"layer-0\" data-z=\"0.5\""));
+        assert!(svg.contains("<g id=\"layer-1\" data-z=\"2\""));
+        assert_eq!(svg.matches("fill-rule=\"evenodd\"").count(), 2);
+        // The second layer has a hole, its path consists of two closed sub-paths.
+        let paths = svg
+            .lines()
+            .filter(|l| l.contains("<path"))
+            .collect::<Vec<_>>();
+        assert_eq!(paths[0].matches('Z').count(), 1);
+        assert_eq!(paths[1].matches('Z').count(), 2);
+        assert!(svg.ends_with("</svg>\n"));
+
+        let mut single = Vec::<u8>::new();
+        write_svg_layer(&mut single, &layers[1], bounds).unwrap();
+        let single = String::from_utf8(single).unwrap();
+        assert_eq!(single.matches("<g ").count(), 1);
+        assert!(single.contains(paths[1]));
+
+        let (min, max) = bounds;
+        for flat in [
+            (min, Vector::new([max[0], min[1], max[2]])),
+            (min, Vector::new([min[0], max[1], max[2]])),
+            (max, min),
+        ] {
+            let err = write_svg_layers(&mut Vec::<u8>::new(), &layers, flat).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    fn volume(mesh: &IndexedMesh) -> f64 {
+        mesh.faces
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertices.map(|i| mesh.vertices[i]);
+                utils::signed_volume(a, b, c)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn triangulate_polygon_with_holes() {
+        // A square with two square holes, given in arbitrary orientation.
+        let outer = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]];
+        let holes = vec![
+            vec![[2., 2.], [4., 2.], [4., 4.], [2., 4.]],
+            vec![[6., 6.], [6., 8.], [8., 8.], [8., 6.]],
+        ];
+        let triangles = triangulate::triangulate(&outer, &holes);
+        assert_eq!(triangles.len(), 12 + 2 * 2 - 2);
+        let points = outer
+            .iter()
+            .chain(holes.iter().flatten())
+            .collect::<Vec<_>>();
+        let areas = triangles
+            .iter()
+            .map(|t| utils::polygon_area(&t.map(|i| *points[i])))
+            .collect::<Vec<_>>();
+        assert!(areas.iter().all(|&a| a > 0.));
+        assert!((areas.iter().sum::<f64>() - 92.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triangulate_keeps_hole_without_clean_bridge() {
+        // The hole crosses the whole boundary, no bridge to it stays inside, but it is still
+        // connected rather than dropped.
+        let outer = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]];
+        let holes = vec![vec![[-2., 4.], [12., 4.], [12., 6.], [-2., 6.]]];
+        let triangles = triangulate::triangulate(&outer, &holes);
+        assert_eq!(triangles.len(), 4 + 4);
+        for i in 4..8 {
+            assert!(triangles.iter().any(|t| t.contains(&i)));
+        }
+    }
+
+    #[test]
+    fn cut_box_into_closed_halves() {
+        let cube = cuboid([0., 0., 0.], [2., 2., 2.]);
+        let (below, above) = cube.cut(&Plane::horizontal(0.5), true);
+        assert!(below.validate().is_ok());
+        assert!(above.validate().is_ok());
+        assert!((volume(&below) - 2.).abs() < 1e-6);
+        assert!((volume(&above) - 6.).abs() < 1e-6);
+
+        // Without caps, both halves have the cross-section as hole.
+        let (below, above) = cube.cut(&Plane::horizontal(0.5), false);
+        assert_eq!(below.boundary_loops().len(), 1);
+        assert_eq!(above.boundary_loops().len(), 1);
+
+        // Cutting through vertices does not create new ones.
+        let (below, above) = cube.cut(&Plane::horizontal(2.), true);
+        assert_eq!(below.vertices, cube.vertices);
+        assert!(below.validate().is_ok());
+        assert!((volume(&below) - 8.).abs() < 1e-6);
+        assert!(above.faces.is_empty());
+        let (below, above) = cube.cut(&Plane::horizontal(0.), true);
+        assert!(below.faces.is_empty());
+        assert_eq!(above, cube);
+    }
+
+    #[test]
+    fn cut_hollow_box_with_cap_holes() {
+        let mut inner = cuboid([1., 1., 1.], [3., 3., 3.]);
+        for face in &mut inner.faces {
+            face.vertices.swap(1, 2);
+        }
+        let hollow = IndexedMesh::merge(&[cuboid([0., 0., 0.], [4., 4., 4.]), inner], None);
+        let plane = Plane::new(Normal::new([0.2, 0.1, 1.]), Vertex::new([2., 2., 2.]));
+        let (below, above) = hollow.cut(&plane, true);
+        assert!(below.validate().is_ok());
+        assert!(above.validate().is_ok());
+        assert!((volume(&below) + volume(&above) - 56.).abs() < 1e-4);
+        assert_eq!(below.connected_components().len(), 1);
+    }
+
+    #[test]
+    fn bvh_queries_on_box() {
+        let bvh = Bvh::new(&cuboid([0., 0., 0.], [2., 2., 2.]));
+        assert_eq!(
+            bvh.bounds(),
+            Some((Vertex::new([0., 0., 0.]), Vertex::new([2., 2., 2.])))
+        );
+        let ray = Ray {
+            origin: Vertex::new([0.5, 0.7, 5.]),
+            direction: Vector::new([0., 0., -1.]),
+        };
+        let hits = bvh.all_hits(&ray);
+        assert_eq!(hits.len(), 2);
+        assert_eq!((hits[0].distance, hits[1].distance), (3., 5.));
+        assert!(!hits[0].back_face && hits[1].back_face);
+        assert_eq!(bvh.first_hit(&ray), Some(hits[0]));
+        let away = Ray {
+            direction: Vector::new([0., 0., 1.]),
+            ..ray
+        };
+        assert_eq!(bvh.first_hit(&away), None);
+
+        let closest = bvh.closest_point(Vertex::new([3., 1., 1.])).unwrap();
+        assert_eq!(closest.point, Vertex::new([2., 1., 1.]));
+        assert_eq!(closest.distance, 1.);
+        let closest = bvh.closest_point(Vertex::new([3., 3., 3.])).unwrap();
+        assert_eq!(closest.point, Vertex::new([2., 2., 2.]));
+
+        // The top two faces, and the side faces touching the top.
+        assert_eq!(
+            bvh.faces_in_box(Vertex::new([0.5, 0.5, 1.9]), Vertex::new([1.5, 1.5, 2.1])),
+            vec![2, 3]
+        );
+        assert_eq!(
+            bvh.faces_in_box(Vertex::new([0.5, 0.5, 0.5]), Vertex::new([1.5, 1.5, 1.5])),
+            Vec::<usize>::new()
+        );
+        assert_eq!(bvh.faces_in_sphere(Vertex::new([1., 1., 1.]), 0.9).len(), 0);
+        assert_eq!(bvh.faces_in_sphere(Vertex::new([1., 1., 3.]), 1.).len(), 2);
+        assert_eq!(bvh.faces_in_sphere(Vertex::new([1., 1., 1.]), 2.).len(), 12);
+
+        assert!(Bvh::new(&mesh_from(&[], &[]))
+            .closest_point(ray.origin)
+            .is_none());
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Bvh>();
+
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let bunny = read_stl(&mut reader).unwrap();
+        let bvh = Bvh::new(&bunny);
+        let (min, max) = bvh.bounds().unwrap();
+        // Deterministic pseudo random points around the bunny.
+        let mut seed = 12345u32;
+        let mut random = |lo: f32, hi: f32| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            lo + (hi - lo) * (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        for _ in 0..50 {
+            let origin = Vertex::new([0, 1, 2].map(|c| random(min[c] - 1., max[c] + 1.)));
+            let target = Vertex::new([0, 1, 2].map(|c| random(min[c], max[c])));
+            let ray = Ray {
+                origin,
+                direction: Vector::new([0, 1, 2].map(|c| target[c] - origin[c])),
+            };
+            let faces = (0..bunny.faces.len())
+                .filter(|&fi| {
+                    let single = IndexedMesh {
+                        vertices: bunny.vertices.clone(),
+                        faces: vec![bunny.faces[fi]],
+                    };
+                    Bvh::new(&single).first_hit(&ray).is_some()
+                })
+                .collect::<Vec<_>>();
+            let mut hits = bvh
+                .all_hits(&ray)
+                .iter()
+                .map(|h| h.face)
+                .collect::<Vec<_>>();
+            hits.sort_unstable();
+            assert_eq!(hits, faces);
+            if let Some(first) = bvh.first_hit(&ray) {
+                assert_eq!(first, bvh.all_hits(&ray)[0]);
+            }
+
+            let closest = bvh.closest_point(origin).unwrap();
+            let brute = bunny
+                .faces
+                .iter()
+                .map(|f| {
+                    let triangle = f.vertices.map(|i| bunny.vertices[i]);
+                    let p = bvh::closest_point_on_triangle(origin, &triangle);
+                    utils::length(utils::sub(p, origin))
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!((closest.distance - brute).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn inside_hollow_box() {
+        let mut inner = cuboid([1., 1., 1.], [3., 3., 3.]);
+        for face in &mut inner.faces {
+            face.vertices.swap(1, 2);
+        }
+        let hollow = IndexedMesh::merge(&[cuboid([0., 0., 0.], [4., 4., 4.]), inner], None);
+        let bvh = Bvh::new(&hollow);
+        assert!(bvh.contains(Vertex::new([0.5, 0.5, 0.5])));
+        assert!(bvh.contains(Vertex::new([3.5, 2., 2.])));
+        assert!(!bvh.contains(Vertex::new([2., 2., 2.])));
+        assert!(!bvh.contains(Vertex::new([5., 2., 2.])));
+        // On the surface, at a vertex and along an edge.
+        assert!(bvh.contains(Vertex::new([4., 4., 4.])));
+        assert!(bvh.contains(Vertex::new([1., 2., 1.])));
+        // Off the surface only by rounding errors.
+        assert!(bvh.contains(Vertex::new([4.000001, 2., 2.])));
+        assert!(!bvh.contains(Vertex::new([4.001, 2., 2.])));
+        assert!((bvh.winding_number(Vertex::new([2., 2., 2.]))).abs() < 1e-9);
+        assert!((bvh.winding_number(Vertex::new([0.5, 2., 2.])) - 1.).abs() < 1e-9);
+
+        assert_eq!(bvh.signed_distance(Vertex::new([2., 2., 2.])), 1.);
+        assert_eq!(bvh.signed_distance(Vertex::new([0.25, 2., 2.])), -0.25);
+        assert_eq!(bvh.signed_distance(Vertex::new([2., 2., 6.])), 2.);
+        assert_eq!(
+            Bvh::new(&mesh_from(&[], &[])).signed_distance(Vertex::new([0., 0., 0.])),
+            f32::INFINITY
+        );
+    }
+
+    #[test]
+    fn winding_number_of_sphere() {
+        let grid = ScalarGrid::from_fn(Vertex::new([-1.5; 3]), 0.0625, [49; 3], |p| {
+            (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt() - 1.
+        });
+        let sphere = grid.marching_cubes(0.).unwrap();
+        assert!(sphere.faces.len() > 10_000);
+        let bvh = Bvh::new(&sphere);
+        // Far away faces are approximated, worst at the center, where all faces are.
+        for (r, expected) in [
+            (0., 1.),
+            (0.5, 1.),
+            (0.9, 1.),
+            (1.1, 0.),
+            (2., 0.),
+            (10., 0.),
+        ] {
+            let winding = bvh.winding_number(Vertex::new([0.6 * r, 0.8 * r, 0.]));
+            assert!((winding - expected).abs() < 0.05, "{} at {}", winding, r);
+        }
+    }
+
+    #[test]
+    fn inside_bunny_matches_ray_parity() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let mut bunny = read_stl(&mut reader).unwrap();
+        bunny.fill_holes(100);
+        let bvh = Bvh::new(&bunny);
+        let (min, max) = bvh.bounds().unwrap();
+        let mut seed = 4711u32;
+        let mut random = |lo: f32, hi: f32| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            lo + (hi - lo) * (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        let mut inside = 0;
+        for _ in 0..100 {
+            let point = Vertex::new([0, 1, 2].map(|c| random(min[c], max[c])));
+            let ray = Ray {
+                origin: point,
+                direction: Vector::new([0.31, 0.47, 0.83]),
+            };
+            let parity = bvh.all_hits(&ray).len() % 2 == 1;
+            assert_eq!(bvh.contains(point), parity);
+            assert_eq!(bvh.signed_distance(point) < 0., parity);
+            inside += usize::from(parity);
+        }
+        assert!(inside > 0);
+    }
+
+    #[test]
+    fn self_intersections_of_overlapping_boxes() {
+        let cube = cuboid([0., 0., 0.], [2., 2., 2.]);
+        assert!(cube.self_intersections().is_empty());
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        assert!(read_stl(&mut reader)
+            .unwrap()
+            .self_intersections()
+            .is_empty());
+
+        // The top of the second box passes through the sides of the first.
+        let boxes = IndexedMesh::merge(&[cube.clone(), cuboid([1., 1., -1.], [3., 3., 1.])], None);
+        let pairs = boxes.self_intersections();
+        assert!(!pairs.is_empty());
+        assert!(pairs.iter().all(|&(i, j)| i < 12 && j >= 12));
+        // Only touching along a face still counts.
+        let touching =
+            IndexedMesh::merge(&[cube.clone(), cuboid([2., 0., 0.], [4., 2., 2.])], None);
+        assert!(!touching.self_intersections().is_empty());
+        let apart = IndexedMesh::merge(&[cube.clone(), cuboid([3., 0., 0.], [5., 2., 2.])], None);
+        assert!(apart.self_intersections().is_empty());
+    }
+
+    #[test]
+    fn self_intersections_of_neighbours() {
+        let vertices = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [0., 1., 0.],
+            [1., 1., 0.],
+            [0.5, 0.2, 0.],
+        ];
+        // Sharing an edge, side by side and folded onto each other.
+        assert!(mesh_from(&vertices, &[[0, 1, 2], [1, 3, 2]])
+            .self_intersections()
+            .is_empty());
+        assert_eq!(
+            mesh_from(&vertices, &[[0, 1, 2], [1, 2, 4]]).self_intersections(),
+            vec![(0, 1)]
+        );
+        // Sharing a vertex, next to each other and overlapping.
+        assert!(mesh_from(&vertices, &[[0, 1, 4], [0, 4, 2]])
+            .self_intersections()
+            .is_empty());
+        assert_eq!(
+            mesh_from(&vertices, &[[0, 1, 2], [0, 4, 3]]).self_intersections(),
+            vec![(0, 1)]
+        );
+        // Sharing a vertex, one face piercing the other.
+        let piercing = mesh_from(
+            &[
+                [0., 0., 0.],
+                [2., 0., 0.],
+                [0., 2., 0.],
+                [1., 1., -1.],
+                [0.2, 0.2, 1.],
+            ],
+            &[[0, 1, 2], [3, 4, 1]],
+        );
+        assert_eq!(piercing.self_intersections(), vec![(0, 1)]);
+        assert!(
+            mesh_from(&vertices, &[[0, 1, 2], [0, 1, 2]])
+                .self_intersections()
+                .len()
+                == 1
+        );
+    }
+
+    #[test]
+    fn orientation_signs_are_exact() {
+        let point = |p: [f32; 3]| p.map(f64::from);
+        // Nearly coplanar points, for which the determinant rounds to zero in double precision.
+        let a = point([0.13840775, 0.6045301, -0.87378633]);
+        let b = point([-7.641626e-21, 5.2192487e-21, -5.5509514e-22]);
+        let c = point([-2.4076956e-21, -5.8009037e-21, -2.4286687e-22]);
+        let d = point([-0.018339498, -0.080102295, 0.11577966]);
+        assert!(predicates::orient3d(a, b, c, d) < 0.0);
+        assert!(predicates::orient3d(a, c, b, d) > 0.0);
+        // Here it even gets the sign wrong.
+        let a = point([0.37296772, 0.9380813, 0.45170522]);
+        let b = point([5.525883e-11, 5.27402e-10, 8.78334e-10]);
+        let c = point([1.05719156e-10, -3.0859917e-10, 3.5369707e-10]);
+        let d = point([-0.69102293, -1.7380478, -0.8369053]);
+        assert!(predicates::orient3d(a, b, c, d) > 0.0);
+        assert!(predicates::orient3d(b, a, c, d) < 0.0);
+    }
+
+    #[test]
+    fn voxelize_box() {
+        let cube = cuboid([0.1, 0.1, 0.1], [1.9, 1.9, 1.9]);
+        let surface = cube.voxelize(0.5, VoxelFill::Surface).unwrap();
+        assert_eq!(surface.origin, Vertex::new([0.1, 0.1, 0.1]));
+        assert_eq!(surface.dimensions, [4, 4, 4]);
+        assert_eq!(surface.count(), 56);
+        assert!(!surface.get([1, 2, 1]));
+        assert!(!surface.get([4, 0, 0]));
+        let solid = cube.voxelize(0.5, VoxelFill::Solid).unwrap();
+        assert_eq!(solid.count(), 64);
+        assert_eq!(solid.center([1, 2, 1]), Vertex::new([0.85, 1.35, 0.85]));
+
+        let sparse = cube.voxelize_sparse(0.5, VoxelFill::Surface).unwrap();
+        assert_eq!(sparse, surface.to_sparse());
+        assert_eq!(sparse.to_dense(), surface);
+
+        // Faces exactly on voxel boundaries touch the voxels on both sides.
+        let aligned = cuboid([0., 0., 0.], [2., 2., 2.])
+            .voxelize(0.5, VoxelFill::Solid)
+            .unwrap();
+        assert_eq!(aligned.dimensions, [5, 5, 5]);
+        assert_eq!(aligned.count(), 125);
+
+        let empty = mesh_from(&[], &[])
+            .voxelize_sparse(0.5, VoxelFill::Solid)
+            .unwrap();
+        assert_eq!(empty.dimensions, [0, 0, 0]);
+        assert!(empty.voxels.is_empty());
+
+        for spacing in [0., -1., f32::NAN, f32::INFINITY] {
+            let error = cube.voxelize(spacing, VoxelFill::Surface).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        let error = cube.voxelize_sparse(1e-30, VoxelFill::Surface).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn voxelize_bunny() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let open = read_stl(&mut reader).unwrap();
+        let mut closed = open.clone();
+        closed.fill_holes(100);
+        // The open bunny is filled by winding numbers in the columns through its base, the
+        // closed one by counting crossings only.
+        for bunny in [open, closed] {
+            let (min, max) = bunny.bounding_box().unwrap();
+            let spacing = (max[0] - min[0]) / 20.;
+            let surface = bunny.voxelize_sparse(spacing, VoxelFill::Surface).unwrap();
+            let solid = bunny.voxelize_sparse(spacing, VoxelFill::Solid).unwrap();
+            assert!(surface.voxels.is_subset(&solid.voxels));
+            assert!(solid.voxels.len() > surface.voxels.len());
+            let bvh = Bvh::new(&bunny);
+            // Away from the surface, voxels are set exactly where the winding number says the
+            // mesh is inside. Only near the open base of the bunny that is ambiguous.
+            let [dx, dy, dz] = solid.dimensions;
+            for voxel in
+                (0..dz).flat_map(|z| (0..dy).flat_map(move |y| (0..dx).map(move |x| [x, y, z])))
+            {
+                let winding = bvh.winding_number(solid.center(voxel));
+                if !surface.get(voxel) && (winding - 0.5).abs() > 0.1 {
+                    assert_eq!(solid.get(voxel), winding > 0.5);
+                }
+            }
+            assert_eq!(
+                bunny
+                    .voxelize(spacing, VoxelFill::Solid)
+                    .unwrap()
+                    .to_sparse(),
+                solid
+            );
+        }
+    }
+
+    #[test]
+    fn marching_cubes_sphere() {
+        let radius = 1.;
+        let grid = ScalarGrid::from_fn(
+            Vertex::new([-1.25, -1.5, -1.75]),
+            0.125,
+            [21, 25, 29],
+            |p| (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt() - radius,
+        );
+        assert_eq!(grid.point([10, 12, 14]), Vertex::new([0., 0., 0.]));
+        let sphere = grid.marching_cubes(0.).unwrap();
+        assert!(sphere.validate().is_ok());
+        // A single closed shell: V - E + F = V - F / 2 = 2.
+        assert_eq!(
+            sphere.vertices.len() as isize - sphere.faces.len() as isize / 2,
+            2
+        );
+        let expected = 4. / 3. * std::f64::consts::PI;
+        assert!((volume(&sphere) - expected).abs() / expected < 0.02);
+        let bvh = Bvh::new(&sphere);
+        assert!(bvh.contains(Vertex::new([0.1, 0.2, 0.3])));
+        assert!(sphere
+            .vertices
+            .iter()
+            .all(|v| (utils::length(*v) - radius).abs() < 0.01));
+    }
+
+    #[test]
+    fn marching_cubes_closes_at_grid_border() {
+        // Everything inside, the surface runs half a spacing outside the grid.
+        let full = ScalarGrid::from_fn(Vertex::new([0., 0., 0.]), 1., [3, 2, 2], |_| -1.);
+        let block = full.marching_cubes(0.).unwrap();
+        assert!(block.validate().is_ok());
+        assert_eq!(
+            block.bounding_box(),
+            Some((
+                Vertex::new([-0.5, -0.5, -0.5]),
+                Vertex::new([2.5, 1.5, 1.5])
+            ))
+        );
+        // The tetrahedra cut off the corners of the grid.
+        assert!(volume(&block) > 6. && volume(&block) < 12.);
+
+        let empty = ScalarGrid::from_fn(Vertex::new([0., 0., 0.]), 1., [3, 2, 2], |_| 1.);
+        assert!(empty.marching_cubes(0.).unwrap().faces.is_empty());
+
+        // NaN samples are outside.
+        let mut holey = full.clone();
+        holey.values[0] = f32::NAN;
+        let mesh = holey.marching_cubes(0.).unwrap();
+        assert!(mesh.validate().is_ok());
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|v| v.0.iter().all(|c| c.is_finite())));
+        assert!(volume(&mesh) < volume(&block));
+
+        let mut short = full.clone();
+        short.values.pop();
+        let err = short.marching_cubes(0.).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let overflowing = ScalarGrid {
+            dimensions: [usize::MAX, 2, 1],
+            values: vec![],
+            ..full
+        };
+        let err = overflowing.marching_cubes(0.).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn voxels_to_mesh() {
+        let mut inner = cuboid([1., 1., 1.], [3., 3., 3.]);
+        for face in &mut inner.faces {
+            face.vertices.swap(1, 2);
+        }
+        let hollow = IndexedMesh::merge(&[cuboid([0., 0., 0.], [4., 4., 4.]), inner], None);
+        let mesh = hollow
+            .voxelize(0.25, VoxelFill::Solid)
+            .unwrap()
+            .to_mesh()
+            .unwrap();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(mesh.connected_components().len(), 2);
+
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let bunny = read_stl(&mut reader).unwrap();
+        let (min, max) = bunny.bounding_box().unwrap();
+        let voxels = bunny
+            .voxelize((max[0] - min[0]) / 20., VoxelFill::Solid)
+            .unwrap();
+        let mesh = voxels.to_mesh().unwrap();
+        assert!(mesh.validate().is_ok());
+        assert!(volume(&mesh) > 0.);
+    }
+
+    #[test]
+    fn primitives_are_closed() {
+        use std::f64::consts::PI;
+        let shapes = [
+            (IndexedMesh::cube(2.), 8.),
+            (
+                IndexedMesh::cuboid(Vertex::new([1., 2., 3.]), Vertex::new([2., 4., 6.])),
+                6.,
+            ),
+            (IndexedMesh::uv_sphere(2., 64, 32), 4. / 3. * PI * 8.),
+            (IndexedMesh::ico_sphere(2., 4), 4. / 3. * PI * 8.),
+            (IndexedMesh::cylinder(1., 3., 128), PI * 3.),
+            (IndexedMesh::cone(1., 3., 128), PI),
+            (IndexedMesh::torus(3., 1., 128, 64), 2. * PI * PI * 3.),
+        ];
+        for (mesh, expected) in &shapes {
+            assert!(mesh.validate().is_ok());
+            assert!(mesh.self_intersections().is_empty());
+            let v = volume(mesh);
+            assert!(
+                (v - expected).abs() / expected < 0.01,
+                "{} vs {}",
+                v,
+                expected
+            );
+            for face in &mesh.faces {
+                let [a, b, c] = face.vertices.map(|i| mesh.vertices[i]);
+                assert_eq!(face.normal, utils::normal(a, b, c));
+            }
+        }
+        // Euler characteristic V - E + F = V - F / 2 of sphere like shapes and of the torus.
+        let euler = |m: &IndexedMesh| m.vertices.len() as isize - m.faces.len() as isize / 2;
+        assert_eq!(euler(&shapes[2].0), 2);
+        assert_eq!(euler(&shapes[3].0), 2);
+        assert_eq!(euler(&shapes[6].0), 0);
+        assert_eq!(shapes[3].0.faces.len(), 20 * 4usize.pow(4));
+
+        // Too low resolutions are raised.
+        assert!(IndexedMesh::uv_sphere(1., 0, 0).validate().is_ok());
+        assert!(IndexedMesh::cylinder(1., 1., 1).validate().is_ok());
+        assert!(IndexedMesh::torus(2., 1., 0, 0).validate().is_ok());
+
+        let plane = IndexedMesh::plane(4., 2., 3);
+        assert_eq!(plane.faces.len(), 18);
+        assert_eq!(plane.boundary_loops().len(), 1);
+        assert!(plane
+            .faces
+            .iter()
+            .all(|f| f.normal == Normal::new([0., 0., 1.])));
+        assert_eq!(
+            plane.bounding_box(),
+            Some((Vertex::new([-2., -1., 0.]), Vertex::new([2., 1., 0.])))
+        );
+    }
+
+    #[test]
+    fn heightmap_relief() {
+        let (width, height) = (7, 5);
+        let heights = (0..width * height)
+            .map(|k| ((k % width) as f32 * 0.7).sin() * ((k / width) as f32).cos())
+            .collect::<Vec<_>>();
+        let relief =
+            IndexedMesh::from_heightmap(&heights, width, height, [0.5, 0.25], 2., 3.).unwrap();
+        assert!(relief.validate().is_ok());
+        assert!(relief.self_intersections().is_empty());
+        assert_eq!(relief.faces.len(), 2 * 6 * 4 + 2 * 20 + 18);
+        assert_eq!(
+            relief
+                .bounding_box()
+                .map(|(min, max)| (min, max[0], max[1])),
+            Some((Vertex::new([0., 0., 0.]), 3., 1.))
+        );
+        let flat = IndexedMesh::from_heightmap(&[1.; 6], 3, 2, [1., 1.], 1., 1.).unwrap();
+        assert!((volume(&flat) - 4.).abs() < 1e-6);
+
+        let error = |heights: &[f32], width, height| {
+            IndexedMesh::from_heightmap(heights, width, height, [1., 1.], 1., 1.)
+                .unwrap_err()
+                .kind()
+        };
+        assert_eq!(error(&[1.; 5], 3, 2), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error(&[1.; 3], 3, 1), std::io::ErrorKind::InvalidInput);
+        assert_eq!(
+            error(&[1., -1., 1., 1.], 2, 2),
+            std::io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn extrude_polygons() {
+        use std::f32::consts::PI;
+        // Clockwise outline with a counter-clockwise hole, the orientation does not matter.
+        let outer = [[0., 0.], [0., 3.], [3., 3.], [3., 0.]];
+        let holes = vec![vec![[1., 1.], [2., 1.], [2., 2.], [1., 2.]]];
+        let plate = IndexedMesh::extrude(&outer, &holes, 2., 0., 1., 1);
+        assert!(plate.validate().is_ok());
+        assert!((volume(&plate) - 16.).abs() < 1e-5);
+        // Holes too short to enclose anything are left out of the walls as well as the caps.
+        let mut degenerate = holes.clone();
+        degenerate.extend([vec![], vec![[0.5, 0.5]], vec![[2.5, 0.5], [2.5, 2.5]]]);
+        assert_eq!(
+            IndexedMesh::extrude(&outer, &degenerate, 2., 0., 1., 1),
+            plate
+        );
+
+        // Twisted and tapered, the volume of a frustum does not depend on the twist.
+        let square = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]];
+        let twisted = IndexedMesh::extrude(&square, &[], 3., PI / 2., 0.5, 16);
+        assert!(twisted.validate().is_ok());
+        assert!(twisted.self_intersections().is_empty());
+        assert_eq!(twisted.vertices.len(), 4 * 17);
+        let frustum = IndexedMesh::extrude(&square, &[], 3., 0., 0.5, 1);
+        assert!((volume(&frustum) - 4. * 3. * (1. + 0.5 + 0.25) / 3.).abs() < 1e-5);
+        let (_, max) = twisted.bounding_box().unwrap();
+        assert!((max[2] - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn revolve_profiles() {
+        use std::f64::consts::PI;
+        let washer = [[1., 0.], [2., 0.], [2., 1.], [1., 1.]];
+        let full = IndexedMesh::revolve(&washer, 2. * std::f32::consts::PI, 256);
+        assert!(full.validate().is_ok());
+        assert!((volume(&full) - 3. * PI).abs() / (3. * PI) < 1e-3);
+        assert_eq!(full.connected_components().len(), 1);
+
+        // Touching the axis gives a closed solid without holes in the middle.
+        let disc = [[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        let cylinder = IndexedMesh::revolve(&disc, 2. * std::f32::consts::PI, 256);
+        assert!(cylinder.validate().is_ok());
+        assert!((volume(&cylinder) - PI).abs() / PI < 1e-3);
+        assert_eq!(cylinder.vertices.len(), 2 + 2 * 256);
+        let cone = IndexedMesh::revolve(&[[0., 0.], [1., 0.], [0., 2.]], 7., 64);
+        assert!(cone.validate().is_ok());
+
+        let half = IndexedMesh::revolve(&disc, std::f32::consts::PI, 128);
+        assert!(half.validate().is_ok());
+        assert!((volume(&half) - PI / 2.).abs() / PI < 1e-3);
+        let (min, _) = half.bounding_box().unwrap();
+        assert!(min[1].abs() < 1e-6);
+        let other_half = IndexedMesh::revolve(&disc, -std::f32::consts::PI, 128);
+        assert!(other_half.validate().is_ok());
+        assert!((volume(&other_half) - PI / 2.).abs() / PI < 1e-3);
+        let (_, max) = other_half.bounding_box().unwrap();
+        assert!(max[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn obj_round_trip_bunny() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let bunny = read_stl(&mut reader).unwrap();
+        let mut obj = Vec::<u8>::new();
+        write_obj(&mut obj, &bunny).unwrap();
+        let read = read_obj(&mut ::std::io::Cursor::new(obj)).unwrap();
+        assert_eq!(read.vertices, bunny.vertices);
+        assert_eq!(
+            read.faces.iter().map(|f| f.vertices).collect::<Vec<_>>(),
+            bunny.faces.iter().map(|f| f.vertices).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn read_obj_polygons_and_groups() {
+        let mut reader = ::std::io::Cursor::new(
+            b"# An L shaped face and a triangle.
+            o first
+            v 0 0 0
+            v 2 0 0
+            v 2 1 0
+            v 1 1 0
+            v 1 2 0
+            v 0 2 0
+            vt 0 0
+            vn 0 0 1
+            f 1//1 2//1 3//1 4//1 5//1 6//1
+            g second part
+            v 0 0 1
+            v 1 0 1
+            v 0 1 1
+            f -3/1 -2/1 -1/1
+            usemtl red
+            g first
+            f 1 3 6"
+                .to_vec(),
+        );
+        let groups = read_obj_groups(&mut reader).unwrap();
+        assert_eq!(
+            groups.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["first", "second part"]
+        );
+        let first = &groups[0].mesh;
+        assert_eq!(first.faces.len(), 5);
+        assert_eq!(first.vertices.len(), 6);
+        let areas = first.faces[..4]
+            .iter()
+            .map(|f| {
+                let [a, b, c] = f.vertices.map(|v| first.vertices[v]);
+                utils::tri_area(a, b, c)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(areas.iter().sum::<f32>(), 3.);
+        assert!(first
+            .faces
+            .iter()
+            .all(|f| f.normal == Normal::new([0., 0., 1.])));
+        assert_eq!(groups[1].mesh.vertices.len(), 3);
+
+        let mut obj = Vec::<u8>::new();
+        write_obj_groups(&mut obj, &groups).unwrap();
+        assert_eq!(
+            read_obj_groups(&mut ::std::io::Cursor::new(obj)).unwrap(),
+            groups
+        );
+
+        for broken in [&b"v 0 0 0\nf 1 2 3"[..], b"v 0 0\n", b"v 0 0 0\nf 1 1"] {
+            let error = read_obj(&mut ::std::io::Cursor::new(broken)).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn ply_round_trip_bunny() {
+        let mut reader = ::std::io::Cursor::new(BUNNY_99);
+        let bunny = read_stl(&mut reader).unwrap();
+        let mut reader = ::std::io::Cursor::new(BUNNY_99_ASCII);
+        let bunny_ascii = read_stl(&mut reader).unwrap();
+        let colors = (0..bunny.faces.len())
+            .map(|i| [i as u8, (i * 7) as u8, 255 - i as u8])
+            .collect::<Vec<_>>();
+        for mesh in [&bunny, &bunny_ascii] {
+            for format in [
+                PlyFormat::Ascii,
+                PlyFormat::BinaryLittleEndian,
+                PlyFormat::BinaryBigEndian,
+            ] {
+                let mut ply = Vec::<u8>::new();
+                write_ply_with_colors(&mut ply, mesh, Some(&colors), format).unwrap();
+                let (read, read_colors) =
+                    read_ply_with_colors(&mut ::std::io::Cursor::new(&ply)).unwrap();
+                assert_eq!(read.vertices, mesh.vertices);
+                assert_eq!(
+                    read.faces.iter().map(|f| f.vertices).collect::<Vec<_>>(),
+                    mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>()
+                );
+                assert_eq!(read_colors, Some(colors.clone()));
+
+                let mut ply = Vec::<u8>::new();
+                write_ply(&mut ply, mesh, format).unwrap();
+                let (_, read_colors) =
+                    read_ply_with_colors(&mut ::std::io::Cursor::new(&ply)).unwrap();
+                assert_eq!(read_colors, None);
+            }
+        }
+        let mut ply = Vec::<u8>::new();
+        let error = write_ply_with_colors(&mut ply, &bunny, Some(&colors[1..]), PlyFormat::Ascii);
+        assert_eq!(error.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn read_ply_skips_extra_properties() {
+        let mut ply = b"ply
+format binary_big_endian 1.0
+comment from a scanner
+element vertex 3
+property double x
+property double y
+property double z
+property list uchar float scores
+element face 1
+property uchar flags
+property list uchar int vertex_index
+property float red
+property float green
+property float blue
+element camera 1
+property short id
+end_header
+"
+        .to_vec();
+        for v in [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]] {
+            for c in v {
+                ply.extend_from_slice(&f64::to_be_bytes(c));
+            }
+            ply.push(2);
+            ply.extend_from_slice(&1f32.to_be_bytes());
+            ply.extend_from_slice(&2f32.to_be_bytes());
+        }
+        ply.extend_from_slice(&[7, 3]);
+        for i in [0i32, 1, 2] {
+            ply.extend_from_slice(&i.to_be_bytes());
+        }
+        for c in [1f32, 0.5, 0.] {
+            ply.extend_from_slice(&c.to_be_bytes());
+        }
+        ply.extend_from_slice(&[0, 42]);
+        let (mesh, colors) = read_ply_with_colors(&mut ::std::io::Cursor::new(&ply)).unwrap();
+        assert_eq!(mesh.vertices[1], Vertex::new([1., 0., 0.]));
+        assert_eq!(mesh.faces[0].vertices, [0, 1, 2]);
+        assert_eq!(mesh.faces[0].normal, Normal::new([0., 0., 1.]));
+        assert_eq!(colors, Some(vec![[255, 128, 0]]));
+
+        // Truncated data.
+        let error = read_ply(&mut ::std::io::Cursor::new(&ply[..ply.len() - 3])).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        let error = read_ply(&mut ::std::io::Cursor::new(b"ply\nformat xml 1.0\n")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_ply_rejects_bad_faces() {
+        let header = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list int int vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+";
+        let read = |face: &str| {
+            let ply = format!("{}{}\n", header, face);
+            read_ply(&mut ::std::io::Cursor::new(ply.as_bytes()))
+        };
+        assert_eq!(read("3 0 1 2").unwrap().faces.len(), 1);
+        for face in ["3 -1 1 2", "3 0 1 3", "3 0 1.5 2", "-3 0 1 2", "2 0 1"] {
+            let error = read(face).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "{}", face);
+        }
+    }
+
+    #[cfg(feature = "threemf")]
+    #[test]
+    fn threemf_roundtrip() {
+        let mut cube = IndexedMesh::cube(2.0);
+        cube.faces.truncate(11);
+        let model = ThreeMfModel {
+            units: Units::Inches,
+            items: vec![
+                ThreeMfItem {
+                    name: "open <cube> & co".to_string(),
+                    mesh: cube,
+                    transform: Transform::IDENTITY,
+                },
+                ThreeMfItem {
+                    name: String::new(),
+                    mesh: IndexedMesh::cube(1.0),
+                    transform: Transform::translation([10.0, 0.0, 0.5])
+                        .then(&Transform::scale([1.0, 2.0, 3.0])),
+                },
+            ],
+        };
+        let mut file = ::std::io::Cursor::new(Vec::new());
+        write_3mf(&mut file, &model).unwrap();
+        let read = read_3mf(&mut file).unwrap();
+        assert_eq!(read, model);
+        let (min, max) = read.to_mesh().bounding_box().unwrap();
+        assert_eq!(min, Vertex::new([-1.0, -1.0, -1.0]));
+        assert_eq!(max, Vertex::new([10.5, 1.0, 3.0]));
+    }
+
+    #[cfg(feature = "threemf")]
+    #[test]
+    fn read_3mf_components() {
+        use std::io::Write;
+        let mut file = ::std::io::Cursor::new(Vec::new());
+        let mut archive = zip::ZipWriter::new(&mut file);
+        let options = zip::write::SimpleFileOptions::default();
+        archive.start_file("_rels/.rels", options).unwrap();
+        archive
+            .write_all(
+                br#"<?xml version="1.0"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="r" Target="/3D/parts.model"
+    Type="http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel"/>
+</Relationships>"#,
+            )
+            .unwrap();
+        archive.start_file("3D/parts.model", options).unwrap();
+        archive
+            .write_all(
+                br#"<?xml version="1.0"?>
+<m:model unit="centimeter" xmlns:m="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+  <!-- <vertex x="9" y="9" z="9"/> -->
+  <m:resources>
+    <m:object id="3" type="model" name="tetra">
+      <m:mesh>
+        <m:vertices>
+          <m:vertex x="0" y="0" z="0"/><m:vertex x="1" y="0" z="0"/>
+          <m:vertex x="0" y="1" z="0"/><m:vertex x='0' y='0' z='1'/>
+        </m:vertices>
+        <m:triangles>
+          <m:triangle v1="0" v2="2" v3="1"/><m:triangle v1="0" v2="1" v3="3"/>
+          <m:triangle v1="1" v2="2" v3="3"/><m:triangle v1="0" v2="3" v3="2"/>
+        </m:triangles>
+      </m:mesh>
+    </m:object>
+    <m:object id="7" name="pair &amp; more">
+      <m:components>
+        <m:component objectid="3"/>
+        <m:component objectid="3" transform="1 0 0 0 1 0 0 0 1 5 0 0"/>
+      </m:components>
+    </m:object>
+  </m:resources>
+  <m:build>
+    <m:item objectid="7" transform="-1 0 0 0 1 0 0 0 1 0 0 2"/>
+  </m:build>
+</m:model>"#,
+            )
+            .unwrap();
+        archive.finish().unwrap();
+
+        let model = read_3mf(&mut file).unwrap();
+        assert_eq!(model.units, Units::Centimeters);
+        assert_eq!(model.items.len(), 1);
+        let item = &model.items[0];
+        assert_eq!(item.name, "pair & more");
+        assert_eq!(item.mesh.faces.len(), 8);
+        assert_eq!(item.mesh.vertices[4], Vertex::new([5.0, 0.0, 0.0]));
+        assert!(item.transform.is_mirroring());
+        let mesh = model.to_mesh();
+        assert!(mesh.validate().is_ok());
+        assert_eq!(
+            mesh.bounding_box().unwrap(),
+            (Vertex::new([-6.0, 0.0, 2.0]), Vertex::new([0.0, 1.0, 3.0]))
+        );
+        assert!(volume(&mesh) > 0.0);
+    }
+
+    // Packs a 3MF model part into a file, at the default path, with the tetrahedron as object 1.
+    #[cfg(feature = "threemf")]
+    fn threemf_file(objects: &str, build: &str) -> ::std::io::Cursor<Vec<u8>> {
+        use std::io::Write;
+        let mut file = ::std::io::Cursor::new(Vec::new());
+        let mut archive = zip::ZipWriter::new(&mut file);
+        archive
+            .start_file("3D/3dmodel.model", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        write!(
+            archive,
+            r#"<model xmlns="http://schemas.microsoft.com/3dmanufacturing/core/2015/02">
+  <resources>
+    <object id="1"><mesh>
+      <vertices>
+        <vertex x="0" y="0" z="0"/><vertex x="1" y="0" z="0"/>
+        <vertex x="0" y="1" z="0"/><vertex x="0" y="0" z="1"/>
+      </vertices>
+      <triangles>
+        <triangle v1="0" v2="2" v3="1"/><triangle v1="0" v2="1" v3="3"/>
+        <triangle v1="1" v2="2" v3="3"/><triangle v1="0" v2="3" v3="2"/>
+      </triangles>
+    </mesh></object>
+    {}
+  </resources>
+  <build>{}</build>
+</model>"#,
+            objects, build
+        )
+        .unwrap();
+        archive.finish().unwrap();
+        file
+    }
+
+    #[cfg(feature = "threemf")]
+    #[test]
+    fn read_3mf_shared_components() {
+        let component = |id: usize, x: f32| {
+            format!(
+                r#"<component objectid="{}" transform="1 0 0 0 1 0 0 0 1 {} 0 0"/>"#,
+                id, x
+            )
+        };
+        // Objects 2 and 3 both use object 1, and object 4 uses both of them.
+        let diamond = format!(
+            r#"<object id="2"><components>{}</components></object>
+            <object id="3"><components>{}{}</components></object>
+            <object id="4"><components>{}{}</components></object>"#,
+            component(1, 0.),
+            component(1, 2.),
+            component(1, 4.),
+            component(2, 0.),
+            component(3, 10.),
+        );
+        let build = r#"<item objectid="4"/><item objectid="4"/><item objectid="1"/>"#;
+        let model = read_3mf(&mut threemf_file(&diamond, build)).unwrap();
+        let mesh = &model.items[0].mesh;
+        assert_eq!(mesh.faces.len(), 3 * 4);
+        assert!(mesh.validate().is_ok());
+        assert_eq!(
+            mesh.bounding_box().unwrap(),
+            (Vertex::new([0., 0., 0.]), Vertex::new([15., 1., 1.]))
+        );
+        assert_eq!(model.items[1], model.items[0]);
+        assert_eq!(model.items[2].mesh.vertices, tetrahedron().vertices);
+        assert_eq!(model.items[2].mesh.faces.len(), 4);
+
+        // Each object listing the next one twice doubles the size with every level.
+        let chain = (2..40)
+            .map(|id| {
+                let next = component(id - 1, 0.);
+                format!(
+                    r#"<object id="{}"><components>{}{}</components></object>"#,
+                    id, next, next
+                )
+            })
+            .collect::<String>();
+        let err = read_3mf(&mut threemf_file(&chain, r#"<item objectid="39"/>"#)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let build = r#"<item objectid="10"/>"#;
+        assert_eq!(
+            read_3mf(&mut threemf_file(&chain, build)).unwrap().items[0]
+                .mesh
+                .faces
+                .len(),
+            4 << 9
+        );
+    }
+
+    #[cfg(feature = "threemf")]
+    #[test]
+    fn read_3mf_rejects_cycles() {
+        let myself =
+            r#"<object id="2"><components><component objectid="2"/></components></object>"#;
+        let cycle = r#"<object id="2"><components><component objectid="1"/>
+            <component objectid="3"/></components></object>
+            <object id="3"><components><component objectid="2"/></components></object>"#;
+        for objects in [myself, cycle] {
+            let err = read_3mf(&mut threemf_file(objects, r#"<item objectid="2"/>"#)).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains("contains itself"));
+        }
+    }
+
+    // Splits a GLB file into its JSON and binary chunk, checking the container layout.
+    fn glb_chunks(glb: &[u8]) -> (String, Vec<u8>) {
+        use byteorder::{ByteOrder, LittleEndian};
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(LittleEndian::read_u32(&glb[4..]), 2);
+        assert_eq!(LittleEndian::read_u32(&glb[8..]) as usize, glb.len());
+        let json_length = LittleEndian::read_u32(&glb[12..]) as usize;
+        assert_eq!(json_length % 4, 0);
+        assert_eq!(&glb[16..20], b"JSON");
+        let json = String::from_utf8(glb[20..20 + json_length].to_vec()).unwrap();
+        let bin = &glb[20 + json_length..];
+        let bin_length = LittleEndian::read_u32(bin) as usize;
+        assert_eq!(&bin[4..8], b"BIN\0");
+        assert_eq!(bin.len(), 8 + bin_length);
+        (json, bin[8..].to_vec())
+    }
+
+    #[test]
+    fn glb_indexed() {
+        use byteorder::{ByteOrder, LittleEndian};
+        let mesh = tetrahedron();
+        let mut glb = Vec::new();
+        write_glb(&mut glb, &mesh, false).unwrap();
+        let (json, bin) = glb_chunks(&glb);
+        assert!(json.contains(r#""asset":{"version":"2.0""#));
+        assert!(json.contains(r#""min":[0,0,0],"max":[1,1,1]"#));
+        assert!(json.contains(r#""indices":1"#));
+        assert!(!json.contains("NORMAL"));
+        assert!(json.contains(&format!(r#""buffers":[{{"byteLength":{}}}]"#, bin.len())));
+        assert_eq!(bin.len(), 4 * 3 * 4 + 4 * 3 * 4);
+        let positions = (0..12)
+            .map(|i| LittleEndian::read_f32(&bin[4 * i..]))
+            .collect::<Vec<_>>();
+        assert_eq!(&positions[9..], &[0., 0., 1.]);
+        let indices = (0..12)
+            .map(|i| LittleEndian::read_u32(&bin[48 + 4 * i..]) as usize)
+            .collect::<Vec<_>>();
+        assert_eq!(&indices[6..9], &mesh.faces[2].vertices);
+
+        assert!(json.contains(r#""nodes":[{"mesh":0,"rotation":[-0.70710677,0,0,0.70710677]}]"#));
+
+        let error = write_glb(&mut Vec::new(), &mesh_from(&[], &[]), false).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        for bad in [f32::NAN, f32::INFINITY] {
+            let mut mesh = tetrahedron();
+            mesh.vertices[2].0[1] = bad;
+            let error = write_glb(&mut Vec::new(), &mesh, true).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn gltf_flat_normals() {
+        use byteorder::{ByteOrder, LittleEndian};
+        let mesh = IndexedMesh::cube(2.0);
+        let (mut gltf, mut bin) = (Vec::new(), Vec::new());
+        write_gltf(&mut gltf, &mut bin, "cube \"1\".bin", &mesh, true).unwrap();
+        let json = String::from_utf8(gltf).unwrap();
+        assert!(json.contains(r#""attributes":{"POSITION":0,"NORMAL":1}"#));
+        assert!(json.contains(r#""count":36,"type":"VEC3","min":[-1,-1,-1],"max":[1,1,1]"#));
+        assert!(json.contains(r#""uri":"cube \"1\".bin""#));
+        assert!(!json.contains("indices"));
+        assert_eq!(bin.len(), 2 * 36 * 12);
+        for (i, face) in mesh.faces.iter().enumerate() {
+            for corner in 0..3 {
+                let offset = 36 * 12 + (3 * i + corner) * 12;
+                let normal = (0..3)
+                    .map(|c| LittleEndian::read_f32(&bin[offset + 4 * c..]))
+                    .collect::<Vec<_>>();
+                assert_eq!(normal, face.normal.0.to_vec());
+            }
+        }
+
+        let mut glb = Vec::new();
+        write_glb(&mut glb, &mesh, true).unwrap();
+        let (glb_json, glb_bin) = glb_chunks(&glb);
+        assert_eq!(glb_bin, bin);
+        assert!(glb_json.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn off_variants() {
+        let mut reader = ::std::io::Cursor::new(
+            b"NOFF 5 2 0
+# vertices with normals
+0 0 0 0 0 1
+1 0 0 0 0 1
+1 1 0 0 0 1
+0 1 0 0 0 1 # trailing comment
+9 9 9 1 0 0
+
+3 0 1 2 255 0 0
+3 0 2 3
+"
+            .to_vec(),
+        );
+        let (mesh, colors) = read_off_with_colors(&mut reader).unwrap();
+        assert_eq!(mesh.vertices.len(), 5);
+        assert_eq!(mesh.faces[1].vertices, [0, 2, 3]);
+        assert_eq!(mesh.faces[1].normal, Normal::new([0., 0., 1.]));
+        assert_eq!(colors, OffColors::default());
+
+        let mut tetrahedron = tetrahedron();
+        for face in &mut tetrahedron.faces {
+            let [a, b, c] = face.vertices.map(|v| tetrahedron.vertices[v]);
+            face.normal = utils::normal(a, b, c);
+        }
+        let colors = OffColors {
+            vertices: Some(vec![[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12]]),
+            faces: Some(vec![[0, 0, 0], [255, 255, 255], [1, 1, 1], [2, 2, 2]]),
+        };
+        let mut off = Vec::new();
+        write_off_with_colors(&mut off, &tetrahedron, &colors).unwrap();
+        assert!(off.starts_with(b"COFF\n4 4 0\n0 0 0 1 2 3 255\n"));
+        let read = read_off_with_colors(&mut ::std::io::Cursor::new(off)).unwrap();
+        assert_eq!(read, (tetrahedron.clone(), colors));
+
+        let error = write_off_with_colors(
+            &mut Vec::new(),
+            &tetrahedron,
+            &OffColors {
+                vertices: None,
+                faces: Some(vec![[0, 0, 0]]),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        for (off, kind) in [
+            (
+                &b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n"[..],
+                std::io::ErrorKind::InvalidData,
+            ),
+            (b"4OFF\n", std::io::ErrorKind::InvalidData),
+            (b"COFF\n1 0 0\n0 0 0\n", std::io::ErrorKind::InvalidData),
+            (b"OFF\n3 1 0\n0 0 0\n", std::io::ErrorKind::UnexpectedEof),
+            // Counts are not trusted for allocations.
+            (
+                b"OFF\n999999999999 1 0\n",
+                std::io::ErrorKind::UnexpectedEof,
+            ),
+            (
+                b"OFF\n0 999999999999 0\n",
+                std::io::ErrorKind::UnexpectedEof,
+            ),
+            (
+                b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n18446744073709551615 0 1 2\n",
+                std::io::ErrorKind::InvalidData,
+            ),
+        ] {
+            let error = read_off(&mut ::std::io::Cursor::new(off)).unwrap_err();
+            assert_eq!(error.kind(), kind);
+        }
+    }
+
+    #[test]
+    fn amf_volumes_and_materials() {
+        let mut reader = ::std::io::Cursor::new(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<amf unit="micron" version="1.1">
+  <metadata type="name">ignored</metadata>
+  <material id="2">
+    <metadata type="name">Red &amp; shiny</metadata>
+    <color><r>1</r><g>0</g><b>0.25</b><a>1</a></color>
+  </material>
+  <material id="3"/>
+  <object id="5">
+    <metadata type="name">tetra</metadata>
+    <color><r>0.5</r><g>0.5</g><b>0.5</b></color>
+    <mesh>
+      <vertices>
+        <vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates>
+          <normal><nx>1</nx><ny>0</ny><nz>0</nz></normal></vertex>
+        <vertex><coordinates><x>1</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>1</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>0</y><z>1e0</z></coordinates></vertex>
+      </vertices>
+      <volume materialid="2">
+        <metadata type="name">red part</metadata>
+        <triangle><v1>0</v1><v2>2</v2><v3>1</v3></triangle>
+      </volume>
+      <volume>
+        <triangle><v1>0</v1><v2>1</v2><v3>3</v3></triangle>
+        <triangle><v1>1</v1><v2>2</v2><v3>3</v3></triangle>
+        <triangle><v1>0</v1><v2>3</v2><v3>2</v3></triangle>
+      </volume>
+    </mesh>
+  </object>
+</amf>"#
+                .to_vec(),
+        );
+        let model = read_amf(&mut reader).unwrap();
+        assert_eq!(model.units, Units::Micrometers);
+        assert_eq!(
+            model.materials,
+            vec![
+                AmfMaterial {
+                    id: 2,
+                    name: "Red & shiny".to_string(),
+                    color: Some([1.0, 0.0, 0.25]),
+                },
+                AmfMaterial {
+                    id: 3,
+                    name: String::new(),
+                    color: None,
+                },
+            ]
+        );
+        let object = &model.objects[0];
+        assert_eq!((object.id, object.name.as_str()), (5, "tetra"));
+        assert_eq!(
+            object.volumes,
+            vec![
+                AmfVolume {
+                    material_id: Some(2),
+                    faces: 0..1,
+                },
+                AmfVolume {
+                    material_id: None,
+                    faces: 1..4,
+                },
+            ]
+        );
+        assert!(object.mesh.validate().is_ok());
+
+        let mut amf = Vec::new();
+        write_amf(&mut amf, &model).unwrap();
+        assert_eq!(read_amf(&mut ::std::io::Cursor::new(amf)).unwrap(), model);
+
+        // Centimeters are converted.
+        let mut cm = model.clone();
+        cm.units = Units::Centimeters;
+        let mut amf = Vec::new();
+        write_amf(&mut amf, &cm).unwrap();
+        let read = read_amf(&mut ::std::io::Cursor::new(amf)).unwrap();
+        assert_eq!(read.units, Units::Millimeters);
+        assert_eq!(read.objects[0].mesh.vertices[3], Vertex::new([0., 0., 10.]));
+
+        let mut broken = model.clone();
+        broken.objects[0].volumes[1].faces = 1..5;
+        let error = write_amf(&mut Vec::new(), &broken).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let error = read_amf(&mut ::std::io::Cursor::new(
+            br#"<amf><object id="1"><mesh><vertices/><volume>
+            <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle></volume></mesh></object></amf>"#,
+        ))
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn amf_zip_roundtrip() {
+        let model = AmfModel::from_mesh(IndexedMesh::cube(3.0), Units::Inches);
+        let mut file = ::std::io::Cursor::new(Vec::new());
+        write_amf_zip(&mut file, &model).unwrap();
+        assert!(file.get_ref().starts_with(b"PK"));
+        file.set_position(0);
+        assert_eq!(read_amf(&mut file).unwrap(), model);
+    }
+
+    #[test]
+    fn load_and_save_by_extension() {
+        let dir = std::env::temp_dir().join(format!("stl_io_formats_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mesh = IndexedMesh::cube(2.0);
+        let mut extensions = vec!["stl", "STL", "obj", "ply", "off", "amf"];
+        if cfg!(feature = "threemf") {
+            extensions.push("3mf");
+        }
+        for extension in extensions {
+            let path = dir.join(format!("cube.{}", extension));
+            save_mesh(&path, &mesh).unwrap();
+            let read = load_mesh(&path).unwrap();
+            assert_eq!(read.faces.len(), 12, "{}", extension);
+            assert_eq!(read.bounding_box(), mesh.bounding_box(), "{}", extension);
+        }
+
+        // Content wins over a wrong or unknown extension.
+        std::fs::copy(dir.join("cube.ply"), dir.join("mislabeled.stl")).unwrap();
+        std::fs::copy(dir.join("cube.off"), dir.join("cube.txt")).unwrap();
+        for name in ["mislabeled.stl", "cube.txt"] {
+            assert_eq!(load_mesh(dir.join(name)).unwrap().faces.len(), 12);
+        }
+        let error = load_mesh(dir.join("cube.obj").with_extension("")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        std::fs::copy(dir.join("cube.obj"), dir.join("cube.dat")).unwrap();
+        let error = load_mesh(dir.join("cube.dat")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        save_mesh(dir.join("cube.glb"), &mesh).unwrap();
+        let error = load_mesh(dir.join("cube.glb")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        // A failing format does not destroy the file.
+        let glb = std::fs::read(dir.join("cube.glb")).unwrap();
+        let empty = IndexedMesh {
+            vertices: vec![],
+            faces: vec![],
+        };
+        assert!(save_mesh(dir.join("cube.glb"), &empty).is_err());
+        assert_eq!(std::fs::read(dir.join("cube.glb")).unwrap(), glb);
+        let error = save_mesh(dir.join("cube.xyz"), &mesh).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn load_and_save_compressed_stl() {
+        let dir = std::env::temp_dir().join(format!("stl_io_compressed_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mesh = IndexedMesh::cube(2.0);
+        let mut files = vec![];
+        if cfg!(feature = "gzip") {
+            files.push(("cube.stl.gz", &[0x1f, 0x8b][..]));
+        }
+        if cfg!(feature = "zstd") {
+            files.push(("Cube.STL.ZST", &[0x28, 0xb5, 0x2f, 0xfd][..]));
+        }
+        for (name, magic) in files {
+            save_mesh(dir.join(name), &mesh).unwrap();
+            let data = std::fs::read(dir.join(name)).unwrap();
+            assert!(data.starts_with(magic), "{}", name);
+            let read = load_mesh(dir.join(name)).unwrap();
+            assert_eq!(read.faces.len(), 12, "{}", name);
+            // Recognized by content as well.
+            std::fs::write(dir.join("cube.bin"), &data).unwrap();
+            assert_eq!(load_mesh(dir.join("cube.bin")).unwrap(), read);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn format_registry_plugins() {
+        // Vertices only, one triangle per line.
+        struct Triangles;
+        impl MeshFormat for Triangles {
+            fn name(&self) -> &str {
+                "triangles"
+            }
+            fn extensions(&self) -> &[&str] {
+                &["tri", "stl"]
+            }
+            fn sniff(&self, data: &[u8]) -> bool {
+                data.starts_with(b"tri\n")
+            }
+            fn read(&self, data: &[u8]) -> Result<IndexedMesh> {
+                let text = String::from_utf8_lossy(data);
+                let mut vertices = Vec::new();
+                for line in text.lines().skip(1) {
+                    let c = line
+                        .split_whitespace()
+                        .map(|t| t.parse::<f32>().unwrap())
+                        .collect::<Vec<_>>();
+                    for v in c.chunks(3) {
+                        vertices.push([v[0], v[1], v[2]]);
+                    }
+                }
+                let faces = (0..vertices.len() / 3)
+                    .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+                    .collect::<Vec<_>>();
+                Ok(mesh_from(&vertices, &faces))
+            }
+        }
+
+        let data = b"tri\n0 0 0 1 0 0 0 1 0\n";
+        let registry = FormatRegistry::default();
+        assert!(registry.detect(data, Some("tri")).is_none());
+        let mut registry = FormatRegistry::default();
+        registry.register(Triangles);
+        assert_eq!(registry.for_extension("stl").unwrap().name(), "triangles");
+        assert_eq!(registry.detect(data, None).unwrap().name(), "triangles");
+        assert_eq!(registry.read(data, Some("tri")).unwrap().faces.len(), 1);
+        // Real STL files are still recognized by their content.
+        assert_eq!(
+            registry.detect(BUNNY_99, Some("stl")).unwrap().name(),
+            "STL"
+        );
+        assert_eq!(registry.read(BUNNY_99_ASCII, None).unwrap().faces.len(), 99);
+        // Only what the STL reader accepts is recognized as ascii STL.
+        for text in [&b" solid cube\nendsolid\n"[..], b"solidworks\n"] {
+            assert!(FormatRegistry::default().detect(text, None).is_none());
+        }
+        assert_eq!(FormatRegistry::new().formats().count(), 0);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn read_gzip_stl() {
+        use std::io::Write;
+        let expected = read_stl(&mut ::std::io::Cursor::new(BUNNY_99)).unwrap();
+        for stl in [BUNNY_99, BUNNY_99_ASCII] {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            encoder.write_all(stl).unwrap();
+            let mut reader = ::std::io::Cursor::new(encoder.finish().unwrap());
+            let header = read_stl_header(&mut reader).unwrap();
+            let plain_header = read_stl_header(&mut ::std::io::Cursor::new(stl)).unwrap();
+            assert_eq!(header, plain_header);
+            let mesh = read_stl(&mut reader).unwrap();
+            assert_eq!(mesh.faces.len(), expected.faces.len());
+        }
+
+        let mut gz = Vec::new();
+        write_stl_gzip(&mut gz, expected.clone().into_triangle_vec().iter()).unwrap();
+        assert!(gz.len() < 84 + 50 * 99);
+        let mut reader = ::std::io::Cursor::new(gz);
+        assert_eq!(
+            create_stl_reader(&mut reader).unwrap().size_hint(),
+            (99, Some(99))
+        );
+        reader.set_position(0);
+        assert_eq!(read_stl(&mut reader).unwrap(), expected);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn read_zstd_stl() {
+        let expected = read_stl(&mut ::std::io::Cursor::new(BUNNY_99)).unwrap();
+        let zst = zstd::encode_all(BUNNY_99_ASCII, 0).unwrap();
+        let mesh = read_stl(&mut ::std::io::Cursor::new(zst)).unwrap();
+        assert_eq!(mesh.faces.len(), expected.faces.len());
+
+        let mut zst = Vec::new();
+        write_stl_zstd(&mut zst, expected.clone().into_triangle_vec().iter()).unwrap();
+        assert_eq!(&zst[..4], &[0x28, 0xb5, 0x2f, 0xfd]);
+        assert_eq!(
+            read_stl(&mut ::std::io::Cursor::new(zst)).unwrap(),
+            expected
+        );
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn read_gzip_stl_without_feature() {
+        // Without the feature, a binary STL whose header happens to start like gzip is read.
+        let mut stl = BUNNY_99.to_vec();
+        stl[..2].copy_from_slice(&[0x1f, 0x8b]);
+        let mut reader = ::std::io::Cursor::new(stl);
+        assert!(read_stl_header(&mut reader).unwrap().starts_with('\u{1f}'));
+        assert_eq!(read_stl(&mut reader).unwrap().faces.len(), 99);
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn stl_zip_bundle() {
+        use std::io::Write;
+        let mut file = ::std::io::Cursor::new(Vec::new());
+        let mut archive = zip::ZipWriter::new(&mut file);
+        let options = zip::write::SimpleFileOptions::default();
+        archive.start_file("readme.txt", options).unwrap();
+        archive.write_all(b"solid not an stl").unwrap();
+        archive.add_directory("parts/", options).unwrap();
+        archive.start_file("parts/BUNNY.STL", options).unwrap();
+        archive.write_all(BUNNY_99_ASCII).unwrap();
+        archive.start_file("binary.stl", options).unwrap();
+        archive.write_all(BUNNY_99).unwrap();
+        archive.start_file("broken.stl", options).unwrap();
+        archive.write_all(b"solid broken\nfacet").unwrap();
+        archive.finish().unwrap();
+
+        let entries = read_stl_zip(file).unwrap();
+        assert_eq!(entries.len(), 3);
+        let entries = entries.collect::<Vec<_>>();
+        let (name, mesh) = entries[0].as_ref().unwrap();
+        assert_eq!(name, "parts/BUNNY.STL");
+        assert_eq!(mesh.faces.len(), 99);
+        assert_eq!(entries[1].as_ref().unwrap().0, "binary.stl");
+        assert!(entries[2].is_err());
+
+        let cube = IndexedMesh::cube(2.0);
+        let mut bundle = ::std::io::Cursor::new(Vec::new());
+        write_stl_zip(&mut bundle, &[("cube.stl", &cube)]).unwrap();
+        let mut entries = read_stl_zip(bundle).unwrap();
+        let (name, mesh) = entries.next().unwrap().unwrap();
+        assert_eq!((name.as_str(), mesh.faces.len()), ("cube.stl", 12));
+        assert!(entries.next().is_none());
+    }
 }
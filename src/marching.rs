@@ -0,0 +1,240 @@
+use crate::types::{IndexedMesh, Vertex};
+use crate::voxel::VoxelGrid;
+use std::collections::HashMap;
+use std::io::Result;
+
+/// Scalar field sampled on a regular grid, for example a signed distance field.
+///
+/// Sample `[x, y, z]` lies at `origin + spacing * [x, y, z]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalarGrid {
+    /// Position of the first sample.
+    pub origin: Vertex,
+    /// Distance between neighbouring samples.
+    pub spacing: f32,
+    /// Number of samples along x, y and z.
+    pub dimensions: [usize; 3],
+    /// The samples, with x varying fastest, then y, then z.
+    pub values: Vec<f32>,
+}
+
+// Crossings closer to a sample than this fraction of the edge are moved away from it, so that no
+// triangle degenerates.
+const MIN_CROSSING: f32 = 0.01;
+
+// The cube from corner 0 to corner 7, with corner i at offset [i & 1, (i >> 1) & 1, i >> 2], split
+// into six positively oriented tetrahedra around its main diagonal. Neighbouring cubes split their
+// common face along the same diagonal, so the tetrahedra of all cubes fit together.
+const TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 3, 2, 7],
+    [0, 2, 6, 7],
+    [0, 6, 4, 7],
+    [0, 4, 5, 7],
+    [0, 5, 1, 7],
+];
+
+impl ScalarGrid {
+    /// Samples a function on a grid.
+    pub fn from_fn<F>(origin: Vertex, spacing: f32, dimensions: [usize; 3], f: F) -> ScalarGrid
+    where
+        F: Fn(Vertex) -> f32,
+    {
+        let [dx, dy, dz] = dimensions;
+        let values = (0..dz)
+            .flat_map(|z| (0..dy).flat_map(move |y| (0..dx).map(move |x| [x, y, z])))
+            .map(|sample| f(sample_point(origin, spacing, sample)))
+            .collect();
+        ScalarGrid {
+            origin,
+            spacing,
+            dimensions,
+            values,
+        }
+    }
+
+    /// Position of a sample in [values](#structfield.values).
+    pub fn index(&self, sample: [usize; 3]) -> usize {
+        sample[0] + self.dimensions[0] * (sample[1] + self.dimensions[1] * sample[2])
+    }
+
+    /// Position of a sample in space.
+    pub fn point(&self, sample: [usize; 3]) -> Vertex {
+        sample_point(self.origin, self.spacing, sample)
+    }
+
+    /// Extracts the surface where the field crosses `iso` as mesh.
+    ///
+    /// Samples below `iso` are inside, like for signed distance fields, and the faces point to
+    /// the outside. The grid is treated as surrounded by outside samples, so the surface is
+    /// closed where the inside reaches the border of the grid. NaN samples count as outside as
+    /// well, with the crossings next to them put halfway along the edge.
+    /// Every cube of eight samples is split into six tetrahedra around its main diagonal, which
+    /// resolves the ambiguous cases of marching cubes consistently between neighbouring cubes.
+    /// Vertices are shared between faces, so the result passes
+    /// [validate](struct.IndexedMesh.html#method.validate), unless the spacing is so small that
+    /// faces become too small for it.
+    ///
+    /// Fails with `InvalidInput` if [values](#structfield.values) does not hold exactly one
+    /// sample per grid point.
+    ///
+    /// ```
+    /// use stl_io::{ScalarGrid, Vertex};
+    /// // A sphere of radius 1.
+    /// let grid = ScalarGrid::from_fn(Vertex::new([-1.5, -1.5, -1.5]), 0.1, [31, 31, 31], |p| {
+    ///     (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt() - 1.0
+    /// });
+    /// let sphere = grid.marching_cubes(0.0).unwrap();
+    /// assert!(sphere.validate().is_ok());
+    /// ```
+    pub fn marching_cubes(&self, iso: f32) -> Result<IndexedMesh> {
+        let samples = self
+            .dimensions
+            .iter()
+            .try_fold(1usize, |n, &d| n.checked_mul(d));
+        if samples != Some(self.values.len()) {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{} values do not match grid dimensions {:?}",
+                    self.values.len(),
+                    self.dimensions
+                ),
+            ));
+        }
+        if self.values.is_empty() {
+            return Ok(IndexedMesh {
+                vertices: Vec::new(),
+                faces: Vec::new(),
+            });
+        }
+        let dimensions = self.dimensions.map(|d| d as isize);
+        let value = |s: [isize; 3]| {
+            if (0..3).all(|c| s[c] >= 0 && s[c] < dimensions[c]) {
+                Some(self.values[self.index(s.map(|c| c as usize))]).filter(|v| !v.is_nan())
+            } else {
+                None
+            }
+        };
+        let inside = |v: Option<f32>| v.is_some_and(|v| v < iso);
+        // Identifies samples including the surrounding layer of virtual ones.
+        let key = |s: [isize; 3]| {
+            ((s[0] + 1) + (dimensions[0] + 2) * ((s[1] + 1) + (dimensions[1] + 2) * (s[2] + 1)))
+                as usize
+        };
+
+        let mut vertices = Vec::new();
+        let mut crossings: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut crossing = |a: [isize; 3], b: [isize; 3]| {
+            let (ka, kb) = (key(a), key(b));
+            *crossings
+                .entry((ka.min(kb), ka.max(kb)))
+                .or_insert_with(|| {
+                    // Virtual samples outside the grid put the crossing halfway, as does an
+                    // interpolation failing on infinite samples.
+                    let t = match (value(a), value(b)) {
+                        (Some(va), Some(vb)) => (iso - va) / (vb - va),
+                        _ => f32::NAN,
+                    };
+                    let t = if t.is_nan() {
+                        0.5
+                    } else {
+                        t.clamp(MIN_CROSSING, 1.0 - MIN_CROSSING)
+                    };
+                    let pa = self.position(a);
+                    let pb = self.position(b);
+                    vertices.push(Vertex::new([0, 1, 2].map(|c| pa[c] + t * (pb[c] - pa[c]))));
+                    vertices.len() - 1
+                })
+        };
+
+        let mut triangles = Vec::new();
+        for z in -1..dimensions[2] {
+            for y in -1..dimensions[1] {
+                for x in -1..dimensions[0] {
+                    let corners = [0, 1, 2, 3, 4, 5, 6, 7]
+                        .map(|i| [x + (i & 1), y + ((i >> 1) & 1), z + (i >> 2)]);
+                    let inside = corners.map(|c| inside(value(c)));
+                    if inside.iter().all(|&i| i) || inside.iter().all(|&i| !i) {
+                        continue;
+                    }
+                    for tetrahedron in TETRAHEDRA {
+                        let mut t = tetrahedron.map(|i| corners[i]);
+                        let mut classes = tetrahedron.map(|i| inside[i]);
+                        // Sort the inside corners first, with swaps in pairs to keep the
+                        // orientation.
+                        let mut swaps = 0;
+                        for i in 0..4 {
+                            for j in (i + 1..4).rev() {
+                                if classes[j] && !classes[j - 1] {
+                                    classes.swap(j, j - 1);
+                                    t.swap(j, j - 1);
+                                    swaps += 1;
+                                }
+                            }
+                        }
+                        let count = classes.iter().filter(|&&c| c).count();
+                        if swaps % 2 == 1 {
+                            // Swap two corners of the same class.
+                            if count >= 2 {
+                                t.swap(0, 1);
+                            } else {
+                                t.swap(2, 3);
+                            }
+                        }
+                        let [a, b, c, d] = t;
+                        match count {
+                            1 => triangles.push([crossing(a, b), crossing(a, c), crossing(a, d)]),
+                            2 => {
+                                let (ac, ad, bc, bd) = (
+                                    crossing(a, c),
+                                    crossing(a, d),
+                                    crossing(b, c),
+                                    crossing(b, d),
+                                );
+                                triangles.push([ac, ad, bd]);
+                                triangles.push([ac, bd, bc]);
+                            }
+                            3 => triangles.push([crossing(d, a), crossing(d, b), crossing(d, c)]),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(super::primitives::mesh_from_faces(vertices, triangles))
+    }
+
+    // Position of a sample, which may lie outside of the grid.
+    fn position(&self, sample: [isize; 3]) -> Vertex {
+        Vertex::new([0, 1, 2].map(|c| self.origin[c] + sample[c] as f32 * self.spacing))
+    }
+}
+
+fn sample_point(origin: Vertex, spacing: f32, sample: [usize; 3]) -> Vertex {
+    Vertex::new([0, 1, 2].map(|c| origin[c] + sample[c] as f32 * spacing))
+}
+
+impl VoxelGrid {
+    /// Converts the voxels into a closed mesh, see
+    /// [ScalarGrid::marching_cubes](struct.ScalarGrid.html#method.marching_cubes). The surface
+    /// runs halfway between the centers of set and unset voxels.
+    ///
+    /// Fails with `InvalidInput` if [voxels](#structfield.voxels) does not hold exactly one
+    /// entry per voxel.
+    pub fn to_mesh(&self) -> Result<IndexedMesh> {
+        let half = self.spacing / 2.0;
+        ScalarGrid {
+            origin: Vertex::new([0, 1, 2].map(|c| self.origin[c] + half)),
+            spacing: self.spacing,
+            dimensions: self.dimensions,
+            values: self
+                .voxels
+                .iter()
+                .map(|&set| if set { -1.0 } else { 1.0 })
+                .collect(),
+        }
+        .marching_cubes(0.0)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::types::{IndexedMesh, Vertex};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+
+/// Named part of an OBJ file, see [read_obj_groups](fn.read_obj_groups.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjGroup {
+    /// Name given by the `g` or `o` statement, `default` for faces before any of them.
+    pub name: String,
+    /// The faces of the group, with only the vertices they use.
+    pub mesh: IndexedMesh,
+}
+
+// Vertices and faces of an OBJ file, faces tagged with the index of their group.
+struct ObjData {
+    vertices: Vec<Vertex>,
+    faces: Vec<([usize; 3], usize)>,
+    groups: Vec<String>,
+}
+
+fn invalid(line: usize, message: String) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("OBJ line {}: {}", line, message),
+    )
+}
+
+fn parse_obj<R: ::std::io::Read>(read: &mut R) -> Result<ObjData> {
+    let mut data = ObjData {
+        vertices: Vec::new(),
+        faces: Vec::new(),
+        groups: vec!["default".to_string()],
+    };
+    let mut group = 0;
+    for (number, line) in BufReader::new(read).lines().enumerate() {
+        let line = line?;
+        let number = number + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut coordinates = [0.0; 3];
+                for c in &mut coordinates {
+                    let token = tokens
+                        .next()
+                        .ok_or_else(|| invalid(number, "vertex needs 3 coordinates".to_string()))?;
+                    *c = token
+                        .parse()
+                        .map_err(|e| invalid(number, format!("{}: {:?}", e, token)))?;
+                }
+                data.vertices.push(Vertex::new(coordinates));
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+                for token in tokens {
+                    // Only the vertex of v/vt/vn is used, normals are computed from the faces.
+                    let index = token.split('/').next().unwrap_or_default();
+                    let index: isize = index
+                        .parse()
+                        .map_err(|e| invalid(number, format!("{}: {:?}", e, token)))?;
+                    let count = data.vertices.len() as isize;
+                    let resolved = if index < 0 { count + index } else { index - 1 };
+                    if index == 0 || resolved < 0 || resolved >= count {
+                        return Err(invalid(
+                            number,
+                            format!("vertex index {} out of range", index),
+                        ));
+                    }
+                    face.push(resolved as usize);
+                }
+                if face.len() < 3 {
+                    return Err(invalid(number, "face needs 3 vertices".to_string()));
+                }
+                for triangle in super::triangulate::triangulate_face(&data.vertices, &face) {
+                    data.faces.push((triangle, group));
+                }
+            }
+            Some("g") | Some("o") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                let name = if name.is_empty() {
+                    "default".to_string()
+                } else {
+                    name
+                };
+                group = match data.groups.iter().position(|g| *g == name) {
+                    Some(g) => g,
+                    None => {
+                        data.groups.push(name);
+                        data.groups.len() - 1
+                    }
+                };
+            }
+            // Texture coordinates, normals, materials and the like are not needed.
+            _ => {}
+        }
+    }
+    Ok(data)
+}
+
+/// Reads the vertices and faces of a Wavefront OBJ file into a single mesh.
+///
+/// Polygons are triangulated, keeping their winding. Texture coordinates, vertex normals,
+/// groups and materials are ignored, the face normals are computed from the vertices.
+/// All vertices are kept in the order of the file, even if no face uses them.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"v 0 0 0
+/// v 1 0 0
+/// v 1 1 0
+/// v 0 1 0
+/// f 1/1/1 2/2/1 3/3/1 4/4/1".to_vec());
+/// let mesh = stl_io::read_obj(&mut reader).unwrap();
+/// assert_eq!(mesh.faces.len(), 2);
+/// ```
+pub fn read_obj<R: ::std::io::Read>(read: &mut R) -> Result<IndexedMesh> {
+    let data = parse_obj(read)?;
+    let faces = data.faces.into_iter().map(|(face, _)| face).collect();
+    Ok(super::primitives::mesh_from_faces(data.vertices, faces))
+}
+
+/// Reads a Wavefront OBJ file like [read_obj](fn.read_obj.html), but returns a mesh for every
+/// group or object with faces, in the order they first appear. Faces of groups that appear
+/// several times are collected in one mesh.
+pub fn read_obj_groups<R: ::std::io::Read>(read: &mut R) -> Result<Vec<ObjGroup>> {
+    let data = parse_obj(read)?;
+    let mut groups = Vec::new();
+    for (gi, name) in data.groups.iter().enumerate() {
+        let mut vertices = Vec::new();
+        let mut index_map = HashMap::new();
+        let faces = data
+            .faces
+            .iter()
+            .filter(|(_, g)| *g == gi)
+            .map(|(face, _)| {
+                face.map(|v| {
+                    *index_map.entry(v).or_insert_with(|| {
+                        vertices.push(data.vertices[v]);
+                        vertices.len() - 1
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        if !faces.is_empty() {
+            groups.push(ObjGroup {
+                name: name.clone(),
+                mesh: super::primitives::mesh_from_faces(vertices, faces),
+            });
+        }
+    }
+    Ok(groups)
+}
+
+/// Writes a mesh as Wavefront OBJ, with its vertices and triangles.
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh::cube(1.0);
+/// let mut obj = Vec::<u8>::new();
+/// stl_io::write_obj(&mut obj, &mesh).unwrap();
+/// let read = stl_io::read_obj(&mut ::std::io::Cursor::new(obj)).unwrap();
+/// assert_eq!(read, mesh);
+/// ```
+pub fn write_obj<W: Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_obj_mesh(&mut writer, mesh, 0)?;
+    writer.flush()
+}
+
+/// Writes several meshes into one Wavefront OBJ file, each as a named group, so that
+/// [read_obj_groups](fn.read_obj_groups.html) reads them back.
+pub fn write_obj_groups<W: Write>(writer: &mut W, groups: &[ObjGroup]) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let mut offset = 0;
+    for group in groups {
+        writeln!(writer, "g {}", group.name)?;
+        write_obj_mesh(&mut writer, &group.mesh, offset)?;
+        offset += group.mesh.vertices.len();
+    }
+    writer.flush()
+}
+
+fn write_obj_mesh<W: Write>(writer: &mut W, mesh: &IndexedMesh, offset: usize) -> Result<()> {
+    for v in &mesh.vertices {
+        writeln!(writer, "v {} {} {}", v[0], v[1], v[2])?;
+    }
+    for face in &mesh.faces {
+        let [a, b, c] = face.vertices.map(|v| v + offset + 1);
+        writeln!(writer, "f {} {} {}", a, b, c)?;
+    }
+    Ok(())
+}
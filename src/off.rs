@@ -0,0 +1,233 @@
+use crate::types::{IndexedMesh, Vertex};
+use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+
+/// Vertex and face colors of an OFF file, see [read_off_with_colors](fn.read_off_with_colors.html).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OffColors {
+    /// A color for every vertex, as stored by the `COFF` variant.
+    pub vertices: Option<Vec<[u8; 3]>>,
+    /// A color for every face.
+    pub faces: Option<Vec<[u8; 3]>>,
+}
+
+fn invalid(line: usize, message: String) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("OFF line {}: {}", line, message),
+    )
+}
+
+// Parses the color at the end of a vertex or face line. Integers are taken as 0..=255, floats
+// as 0..=1, an alpha value is ignored. Single color map indices are not supported.
+fn parse_color(line: usize, tokens: &[&str]) -> Result<Option<[u8; 3]>> {
+    if tokens.len() < 3 {
+        return Ok(None);
+    }
+    let float = tokens[..3].iter().any(|t| t.contains(['.', 'e', 'E']));
+    let mut color = [0; 3];
+    for (c, token) in color.iter_mut().zip(tokens) {
+        let value: f32 = token
+            .parse()
+            .map_err(|e| invalid(line, format!("{}: {:?}", e, token)))?;
+        let value = if float { value * 255.0 } else { value };
+        *c = value.round().clamp(0.0, 255.0) as u8;
+    }
+    Ok(Some(color))
+}
+
+/// Reads the vertices and faces of an OFF file, see
+/// [read_off_with_colors](fn.read_off_with_colors.html).
+pub fn read_off<R: ::std::io::Read>(read: &mut R) -> Result<IndexedMesh> {
+    Ok(read_off_with_colors(read)?.0)
+}
+
+/// Reads the vertices and faces of an OFF file, together with their colors.
+///
+/// The header may be `OFF`, `COFF` for colored vertices, `NOFF` for vertex normals, which are
+/// ignored, or `CNOFF`, and may be followed by the counts on the same line. Faces can carry a
+/// color after their vertex indices, face colors are only returned if every face has one.
+/// Polygons are triangulated, keeping their winding, and every triangle gets the color of its
+/// polygon. The face normals are computed from the vertices.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"COFF
+/// 4 1 0 # a colored square
+/// 0 0 0 255 0 0 255
+/// 1 0 0 0 255 0 255
+/// 1 1 0 0 0 255 255
+/// 0 1 0 255 255 255 255
+/// 4 0 1 2 3 0.5 0.5 0.5
+/// ".to_vec());
+/// let (mesh, colors) = stl_io::read_off_with_colors(&mut reader).unwrap();
+/// assert_eq!(mesh.faces.len(), 2);
+/// assert_eq!(colors.vertices.unwrap()[1], [0, 255, 0]);
+/// assert_eq!(colors.faces, Some(vec![[128, 128, 128]; 2]));
+/// ```
+pub fn read_off_with_colors<R: ::std::io::Read>(read: &mut R) -> Result<(IndexedMesh, OffColors)> {
+    let mut lines = Vec::new();
+    for (number, line) in BufReader::new(read).lines().enumerate() {
+        let line = line?;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if !line.is_empty() {
+            lines.push((number + 1, line.to_string()));
+        }
+    }
+    let mut lines = lines.into_iter();
+    let eof = || ::std::io::Error::new(::std::io::ErrorKind::UnexpectedEof, "OFF: file ends early");
+
+    let (number, header) = lines.next().ok_or_else(eof)?;
+    let mut tokens = header.split_whitespace();
+    let keyword = tokens.next().unwrap_or_default();
+    let (colored, normals) = match keyword {
+        "OFF" => (false, false),
+        "COFF" => (true, false),
+        "NOFF" => (false, true),
+        "CNOFF" => (true, true),
+        _ => return Err(invalid(number, format!("unsupported header {:?}", keyword))),
+    };
+    let mut counts = tokens.map(|t| (number, t.to_string())).collect::<Vec<_>>();
+    if counts.is_empty() {
+        let (number, line) = lines.next().ok_or_else(eof)?;
+        counts = line
+            .split_whitespace()
+            .map(|t| (number, t.to_string()))
+            .collect();
+    }
+    let count = |k: usize| -> Result<usize> {
+        let (number, token) = counts
+            .get(k)
+            .ok_or_else(|| invalid(number, "expected vertex and face counts".to_string()))?;
+        token
+            .parse()
+            .map_err(|e| invalid(*number, format!("{}: {:?}", e, token)))
+    };
+    let (vertex_count, face_count) = (count(0)?, count(1)?);
+
+    // The counts are not trusted to reserve memory, a short file fails once it runs out of lines.
+    let mut vertices = Vec::new();
+    let mut vertex_colors = Vec::new();
+    for _ in 0..vertex_count {
+        let (number, line) = lines.next().ok_or_else(eof)?;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        if tokens.len() < 3 {
+            return Err(invalid(number, "vertex needs 3 coordinates".to_string()));
+        }
+        let mut coordinates = [0.0; 3];
+        for (c, token) in coordinates.iter_mut().zip(&tokens) {
+            *c = token
+                .parse()
+                .map_err(|e| invalid(number, format!("{}: {:?}", e, token)))?;
+        }
+        vertices.push(Vertex::new(coordinates));
+        if colored {
+            let rest = &tokens[(if normals { 6 } else { 3 }).min(tokens.len())..];
+            vertex_colors.push(
+                parse_color(number, rest)?
+                    .ok_or_else(|| invalid(number, "vertex needs a color".to_string()))?,
+            );
+        }
+    }
+
+    let mut faces = Vec::new();
+    let mut face_colors = Vec::new();
+    for _ in 0..face_count {
+        let (number, line) = lines.next().ok_or_else(eof)?;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let n: usize = tokens[0]
+            .parse()
+            .map_err(|e| invalid(number, format!("{}: {:?}", e, tokens[0])))?;
+        if n < 3 || n >= tokens.len() {
+            return Err(invalid(number, "face needs 3 vertices".to_string()));
+        }
+        let mut face = Vec::with_capacity(n);
+        for token in &tokens[1..=n] {
+            let index: usize = token
+                .parse()
+                .map_err(|e| invalid(number, format!("{}: {:?}", e, token)))?;
+            if index >= vertices.len() {
+                return Err(invalid(
+                    number,
+                    format!("vertex index {} out of range", index),
+                ));
+            }
+            face.push(index);
+        }
+        let color = parse_color(number, &tokens[n + 1..])?;
+        for triangle in super::triangulate::triangulate_face(&vertices, &face) {
+            faces.push(triangle);
+            face_colors.push(color);
+        }
+    }
+
+    let colors = OffColors {
+        vertices: if colored { Some(vertex_colors) } else { None },
+        faces: if face_colors.is_empty() {
+            None
+        } else {
+            face_colors.into_iter().collect()
+        },
+    };
+    Ok((super::primitives::mesh_from_faces(vertices, faces), colors))
+}
+
+/// Writes a mesh as OFF, see [write_off_with_colors](fn.write_off_with_colors.html).
+///
+/// ```
+/// let mesh = stl_io::IndexedMesh::cube(1.0);
+/// let mut off = Vec::<u8>::new();
+/// stl_io::write_off(&mut off, &mesh).unwrap();
+/// let read = stl_io::read_off(&mut ::std::io::Cursor::new(off)).unwrap();
+/// assert_eq!(read, mesh);
+/// ```
+pub fn write_off<W: Write>(writer: &mut W, mesh: &IndexedMesh) -> Result<()> {
+    write_off_with_colors(writer, mesh, &OffColors::default())
+}
+
+/// Writes a mesh as OFF with the given colors, as `COFF` if there are vertex colors. Colors are
+/// written as integers with an opaque alpha value. Fails if the number of colors does not match
+/// the number of vertices or faces.
+pub fn write_off_with_colors<W: Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    colors: &OffColors,
+) -> Result<()> {
+    for (given, expected, what) in [
+        (&colors.vertices, mesh.vertices.len(), "vertices"),
+        (&colors.faces, mesh.faces.len(), "faces"),
+    ] {
+        if let Some(given) = given {
+            if given.len() != expected {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidInput,
+                    format!("got {} colors for {} {}", given.len(), expected, what),
+                ));
+            }
+        }
+    }
+    let mut writer = BufWriter::new(writer);
+    let header = if colors.vertices.is_some() {
+        "COFF"
+    } else {
+        "OFF"
+    };
+    writeln!(writer, "{}", header)?;
+    writeln!(writer, "{} {} 0", mesh.vertices.len(), mesh.faces.len())?;
+    for (i, v) in mesh.vertices.iter().enumerate() {
+        write!(writer, "{} {} {}", v[0], v[1], v[2])?;
+        if let Some(colors) = &colors.vertices {
+            let [r, g, b] = colors[i];
+            write!(writer, " {} {} {} 255", r, g, b)?;
+        }
+        writeln!(writer)?;
+    }
+    for (i, face) in mesh.faces.iter().enumerate() {
+        let [a, b, c] = face.vertices;
+        write!(writer, "3 {} {} {}", a, b, c)?;
+        if let Some(colors) = &colors.faces {
+            let [r, g, b] = colors[i];
+            write!(writer, " {} {} {} 255", r, g, b)?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
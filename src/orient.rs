@@ -0,0 +1,122 @@
+use crate::types::{IndexedMesh, IndexedTriangle};
+
+/// Result of [IndexedMesh::orient_faces](struct.IndexedMesh.html#method.orient_faces).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrientationReport {
+    /// Number of faces whose winding was reversed.
+    pub flipped_faces: usize,
+    /// Number of edge-connected components found in the mesh.
+    pub components: usize,
+    /// Face indices of every component that could not be oriented consistently, e.g. because
+    /// it is a Möbius strip or has non-manifold edges with conflicting neighbors.
+    /// Those components are left in a best-effort state.
+    pub non_orientable_components: Vec<Vec<usize>>,
+}
+
+// Reverses the winding of a face and its normal.
+pub(crate) fn flip_face(face: &mut IndexedTriangle) {
+    face.vertices.swap(1, 2);
+    for c in &mut face.normal.0 {
+        *c = -*c;
+    }
+}
+
+impl IndexedMesh {
+    /// Makes the winding of all faces consistent and points the normals outward.
+    ///
+    /// The winding of the first face of every edge-connected component is propagated to its
+    /// neighbors, so that neighboring faces traverse their shared edge in opposite directions.
+    /// Afterwards every component with a negative signed volume is flipped as a whole.
+    /// Face normals are negated together with the winding, but not recomputed.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let mut mesh = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 1.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 3, 1]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// assert!(mesh.validate().is_err());
+    /// let report = mesh.orient_faces();
+    /// assert_eq!(report.flipped_faces, 1);
+    /// assert!(mesh.validate().is_ok());
+    /// ```
+    pub fn orient_faces(&mut self) -> OrientationReport {
+        let topology = self.topology();
+
+        let mut report = OrientationReport::default();
+        let mut visited = vec![false; self.faces.len()];
+        let mut flip = vec![false; self.faces.len()];
+        for seed in 0..self.faces.len() {
+            if visited[seed] {
+                continue;
+            }
+            report.components += 1;
+            visited[seed] = true;
+            let mut component = vec![seed];
+            let mut orientable = true;
+            let mut stack = vec![seed];
+            while let Some(fi) = stack.pop() {
+                let face = self.faces[fi];
+                for i in 0..3 {
+                    // The directed edge as fi will traverse it after flipping.
+                    let (mut u, mut v) = (face.vertices[i], face.vertices[(i + 1) % 3]);
+                    if flip[fi] {
+                        ::std::mem::swap(&mut u, &mut v);
+                    }
+                    for &h in topology.edge_half_edges(u, v) {
+                        let gi = topology.half_edge_face(h);
+                        if gi == fi {
+                            continue;
+                        }
+                        let needs_flip = topology.half_edge_vertices(h) == (u, v);
+                        if !visited[gi] {
+                            visited[gi] = true;
+                            flip[gi] = needs_flip;
+                            component.push(gi);
+                            stack.push(gi);
+                        } else if flip[gi] != needs_flip {
+                            orientable = false;
+                        }
+                    }
+                }
+            }
+
+            let mut volume = 0.0;
+            for &fi in &component {
+                let contribution = super::utils::signed_volume(
+                    self.vertices[self.faces[fi].vertices[0]],
+                    self.vertices[self.faces[fi].vertices[1]],
+                    self.vertices[self.faces[fi].vertices[2]],
+                );
+                volume += if flip[fi] {
+                    -contribution
+                } else {
+                    contribution
+                };
+            }
+            if volume < 0.0 {
+                for &fi in &component {
+                    flip[fi] = !flip[fi];
+                }
+            }
+            if !orientable {
+                component.sort_unstable();
+                report.non_orientable_components.push(component);
+            }
+        }
+
+        for (face, flip) in self.faces.iter_mut().zip(flip) {
+            if flip {
+                flip_face(face);
+                report.flipped_faces += 1;
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,407 @@
+use crate::types::{IndexedMesh, IndexedTriangle, Vertex};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::{BufRead, BufReader, BufWriter, Read, Result, Write};
+
+/// Encoding of the data in a PLY file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlyFormat {
+    /// Human readable text.
+    Ascii,
+    /// Binary, least significant byte first.
+    BinaryLittleEndian,
+    /// Binary, most significant byte first.
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScalarType {
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<ScalarType> {
+        Some(match name {
+            "char" | "int8" => ScalarType::Int8,
+            "uchar" | "uint8" => ScalarType::Uint8,
+            "short" | "int16" => ScalarType::Int16,
+            "ushort" | "uint16" => ScalarType::Uint16,
+            "int" | "int32" => ScalarType::Int32,
+            "uint" | "uint32" => ScalarType::Uint32,
+            "float" | "float32" => ScalarType::Float32,
+            "double" | "float64" => ScalarType::Float64,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Property {
+    name: String,
+    // Type of the count for list properties.
+    count: Option<ScalarType>,
+    value: ScalarType,
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+fn invalid<S: Into<String>>(message: S) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("PLY: {}", message.into()),
+    )
+}
+
+fn read_header<R: BufRead>(reader: &mut R) -> Result<(PlyFormat, Vec<Element>)> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim_end() != "ply" {
+        return Err(invalid("file does not start with \"ply\""));
+    }
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::UnexpectedEof,
+                "PLY: header ends without end_header",
+            ));
+        }
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            ["format", name, _] => {
+                format = Some(match *name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(invalid(format!("unknown format {:?}", name))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid(format!("bad element count {:?}", count)))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, value, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid("property before element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    count: Some(
+                        ScalarType::parse(count)
+                            .ok_or_else(|| invalid(format!("unknown type {:?}", count)))?,
+                    ),
+                    value: ScalarType::parse(value)
+                        .ok_or_else(|| invalid(format!("unknown type {:?}", value)))?,
+                });
+            }
+            ["property", value, name] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| invalid("property before element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    count: None,
+                    value: ScalarType::parse(value)
+                        .ok_or_else(|| invalid(format!("unknown type {:?}", value)))?,
+                });
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(invalid(format!("unexpected header line {:?}", line.trim()))),
+        }
+    }
+    let format = format.ok_or_else(|| invalid("header has no format"))?;
+    Ok((format, elements))
+}
+
+// Reads the values of the body one by one, whatever the encoding.
+enum Values<R> {
+    Ascii(::std::vec::IntoIter<String>),
+    Binary(R, PlyFormat),
+}
+
+impl<R: Read> Values<R> {
+    fn next(&mut self, ty: ScalarType) -> Result<f64> {
+        match self {
+            Values::Ascii(tokens) => {
+                let token = tokens.next().ok_or_else(|| {
+                    ::std::io::Error::new(
+                        ::std::io::ErrorKind::UnexpectedEof,
+                        "PLY: data ends early",
+                    )
+                })?;
+                token
+                    .parse()
+                    .map_err(|_| invalid(format!("bad value {:?}", token)))
+            }
+            Values::Binary(reader, PlyFormat::BinaryBigEndian) => {
+                read_binary::<BigEndian, R>(reader, ty)
+            }
+            Values::Binary(reader, _) => read_binary::<LittleEndian, R>(reader, ty),
+        }
+    }
+}
+
+fn read_binary<B: ByteOrder, R: Read>(reader: &mut R, ty: ScalarType) -> Result<f64> {
+    Ok(match ty {
+        ScalarType::Int8 => f64::from(reader.read_i8()?),
+        ScalarType::Uint8 => f64::from(reader.read_u8()?),
+        ScalarType::Int16 => f64::from(reader.read_i16::<B>()?),
+        ScalarType::Uint16 => f64::from(reader.read_u16::<B>()?),
+        ScalarType::Int32 => f64::from(reader.read_i32::<B>()?),
+        ScalarType::Uint32 => f64::from(reader.read_u32::<B>()?),
+        ScalarType::Float32 => f64::from(reader.read_f32::<B>()?),
+        ScalarType::Float64 => reader.read_f64::<B>()?,
+    })
+}
+
+/// Reads the vertices and faces of a PLY file in any of its encodings, see
+/// [read_ply_with_colors](fn.read_ply_with_colors.html).
+pub fn read_ply<R: Read>(read: &mut R) -> Result<IndexedMesh> {
+    Ok(read_ply_with_colors(read)?.0)
+}
+
+/// Reads the vertices and faces of a PLY file, together with the face colors if the faces have
+/// `red`, `green` and `blue` properties.
+///
+/// Polygons are triangulated, keeping their winding, and every triangle gets the color of its
+/// polygon. Other properties and elements are skipped. The face normals are computed from the
+/// vertices.
+///
+/// ```
+/// let mut reader = ::std::io::Cursor::new(b"ply
+/// format ascii 1.0
+/// element vertex 4
+/// property float x
+/// property float y
+/// property float z
+/// property float confidence
+/// element face 1
+/// property list uchar int vertex_indices
+/// property uchar red
+/// property uchar green
+/// property uchar blue
+/// end_header
+/// 0 0 0 0.5
+/// 1 0 0 0.5
+/// 1 1 0 0.5
+/// 0 1 0 0.5
+/// 4 0 1 2 3 255 128 0
+/// ".to_vec());
+/// let (mesh, colors) = stl_io::read_ply_with_colors(&mut reader).unwrap();
+/// assert_eq!(mesh.faces.len(), 2);
+/// assert_eq!(colors, Some(vec![[255, 128, 0]; 2]));
+/// ```
+pub fn read_ply_with_colors<R: Read>(read: &mut R) -> Result<(IndexedMesh, Option<Vec<[u8; 3]>>)> {
+    let mut reader = BufReader::new(read);
+    let (format, elements) = read_header(&mut reader)?;
+    let mut values = match format {
+        PlyFormat::Ascii => {
+            let mut body = String::new();
+            reader.read_to_string(&mut body)?;
+            Values::Ascii(
+                body.split_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            )
+        }
+        _ => Values::Binary(reader, format),
+    };
+
+    let mut vertices = Vec::new();
+    let mut polygons = Vec::new();
+    let mut polygon_colors = Vec::new();
+    let mut has_colors = false;
+    for element in &elements {
+        let position = |name: &str| element.properties.iter().position(|p| p.name == name);
+        let coordinates = ["x", "y", "z"].map(position);
+        let indices = position("vertex_indices").or_else(|| position("vertex_index"));
+        let channels = ["red", "green", "blue"].map(position);
+        let is_vertex = element.name == "vertex";
+        let is_face = element.name == "face";
+        if is_vertex && coordinates.iter().any(Option::is_none) {
+            return Err(invalid("vertex element lacks x, y or z"));
+        }
+        if is_face && indices.is_none() {
+            return Err(invalid("face element lacks vertex_indices"));
+        }
+        has_colors |= is_face && channels.iter().all(Option::is_some);
+
+        for _ in 0..element.count {
+            let mut vertex = [0.0; 3];
+            let mut polygon = Vec::new();
+            let mut color = [0u8; 3];
+            for (pi, property) in element.properties.iter().enumerate() {
+                let Some(count) = property.count else {
+                    let value = values.next(property.value)?;
+                    if let Some(c) = coordinates.iter().position(|&p| p == Some(pi)) {
+                        vertex[c] = value as f32;
+                    }
+                    if let Some(c) = channels.iter().position(|&p| p == Some(pi)) {
+                        color[c] = match property.value {
+                            ScalarType::Float32 | ScalarType::Float64 => {
+                                (value * 255.0).round().clamp(0.0, 255.0) as u8
+                            }
+                            _ => value.clamp(0.0, 255.0) as u8,
+                        };
+                    }
+                    continue;
+                };
+                let count = values.next(count)?;
+                if count < 0.0 || count.fract() != 0.0 {
+                    return Err(invalid(format!("bad list length {}", count)));
+                }
+                for _ in 0..count as usize {
+                    let value = values.next(property.value)?;
+                    if Some(pi) == indices {
+                        if value < 0.0 || value.fract() != 0.0 {
+                            return Err(invalid(format!("bad vertex index {}", value)));
+                        }
+                        polygon.push(value as usize);
+                    }
+                }
+            }
+            if is_vertex {
+                vertices.push(Vertex::new(vertex));
+            } else if is_face {
+                polygons.push(polygon);
+                polygon_colors.push(color);
+            }
+        }
+    }
+
+    let mut faces = Vec::new();
+    let mut colors = Vec::new();
+    for (polygon, color) in polygons.iter().zip(polygon_colors) {
+        if polygon.len() < 3 || polygon.iter().any(|&v| v >= vertices.len()) {
+            return Err(invalid(format!("invalid face {:?}", polygon)));
+        }
+        for triangle in super::triangulate::triangulate_face(&vertices, polygon) {
+            faces.push(triangle);
+            colors.push(color);
+        }
+    }
+    let mesh = super::primitives::mesh_from_faces(vertices, faces);
+    Ok((mesh, if has_colors { Some(colors) } else { None }))
+}
+
+/// Writes a mesh as PLY in the given encoding.
+///
+/// ```
+/// use stl_io::{IndexedMesh, PlyFormat};
+/// let cube = IndexedMesh::cube(1.0);
+/// let mut ply = Vec::<u8>::new();
+/// stl_io::write_ply(&mut ply, &cube, PlyFormat::BinaryLittleEndian).unwrap();
+/// assert_eq!(stl_io::read_ply(&mut ::std::io::Cursor::new(ply)).unwrap(), cube);
+/// ```
+pub fn write_ply<W: Write>(writer: &mut W, mesh: &IndexedMesh, format: PlyFormat) -> Result<()> {
+    write_ply_with_colors(writer, mesh, None, format)
+}
+
+/// Writes a mesh as PLY in the given encoding, with a color for every face if `colors` is
+/// given. Fails if there are more or less colors than faces, or more vertices than the `uint`
+/// indices can address.
+pub fn write_ply_with_colors<W: Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    colors: Option<&[[u8; 3]]>,
+    format: PlyFormat,
+) -> Result<()> {
+    if let Some(colors) = colors {
+        if colors.len() != mesh.faces.len() {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!("got {} colors for {} faces", colors.len(), mesh.faces.len()),
+            ));
+        }
+    }
+    // Vertex indices are declared as uint.
+    if mesh.vertices.len() > u32::MAX as usize {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("too many vertices for PLY: {}", mesh.vertices.len()),
+        ));
+    }
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, "ply")?;
+    let name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    writeln!(writer, "format {} 1.0", name)?;
+    writeln!(writer, "element vertex {}", mesh.vertices.len())?;
+    for c in ["x", "y", "z"] {
+        writeln!(writer, "property float {}", c)?;
+    }
+    writeln!(writer, "element face {}", mesh.faces.len())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    if colors.is_some() {
+        for c in ["red", "green", "blue"] {
+            writeln!(writer, "property uchar {}", c)?;
+        }
+    }
+    writeln!(writer, "end_header")?;
+
+    match format {
+        PlyFormat::Ascii => {
+            for v in &mesh.vertices {
+                writeln!(writer, "{} {} {}", v[0], v[1], v[2])?;
+            }
+            for (fi, face) in mesh.faces.iter().enumerate() {
+                let [a, b, c] = face.vertices;
+                write!(writer, "3 {} {} {}", a, b, c)?;
+                if let Some(colors) = colors {
+                    let [r, g, b] = colors[fi];
+                    write!(writer, " {} {} {}", r, g, b)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        PlyFormat::BinaryLittleEndian => {
+            write_binary::<LittleEndian, _>(&mut writer, mesh, colors)?
+        }
+        PlyFormat::BinaryBigEndian => write_binary::<BigEndian, _>(&mut writer, mesh, colors)?,
+    }
+    writer.flush()
+}
+
+fn write_binary<B: ByteOrder, W: Write>(
+    writer: &mut W,
+    mesh: &IndexedMesh,
+    colors: Option<&[[u8; 3]]>,
+) -> Result<()> {
+    for v in &mesh.vertices {
+        for &c in &v.0 {
+            writer.write_f32::<B>(c)?;
+        }
+    }
+    for (fi, face) in mesh.faces.iter().enumerate() {
+        let IndexedTriangle { vertices, .. } = face;
+        writer.write_u8(3)?;
+        for &v in vertices {
+            writer.write_u32::<B>(v as u32)?;
+        }
+        if let Some(colors) = colors {
+            writer.write_all(&colors[fi])?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+// Orientation predicates with exactly rounded signs, after Shewchuk's "Adaptive Precision
+// Floating-Point Arithmetic and Fast Robust Geometric Predicates". The determinant is first
+// evaluated in double precision and returned if it is larger than the bound on its rounding error.
+// Otherwise it is evaluated exactly as an expansion: a sum of non-overlapping doubles of
+// increasing magnitude.
+//
+// The exact evaluation assumes coordinates converted from f32, whose products can neither
+// overflow nor underflow in f64.
+
+const EPSILON: f64 = f64::EPSILON / 2.0;
+const ORIENT2D_BOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const ORIENT3D_BOUND: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+
+// Returns the rounded sum and its rounding error, which add up to exactly a + b.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+
+// Returns the rounded product and its rounding error, which add up to exactly a * b.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+// Adds b to the expansion e.
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut sum = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &x in e {
+        let (s, error) = two_sum(q, x);
+        if error != 0.0 {
+            sum.push(error);
+        }
+        q = s;
+    }
+    sum.push(q);
+    sum
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |sum, &x| grow(&sum, x))
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut product = vec![];
+    for &y in f {
+        for &x in e {
+            let (p, error) = two_product(x, y);
+            product = grow(&grow(&product, error), p);
+        }
+    }
+    product
+}
+
+fn difference(a: f64, b: f64) -> Vec<f64> {
+    grow(&[a], -b)
+}
+
+fn negate(e: &[f64]) -> Vec<f64> {
+    e.iter().map(|&x| -x).collect()
+}
+
+// The largest component of an expansion has the sign of its exact value.
+fn approximate(e: &[f64]) -> f64 {
+    e.iter().rev().copied().find(|&x| x != 0.0).unwrap_or(0.0)
+}
+
+/// Twice the signed area of the triangle abc, positive if it is counter-clockwise. The sign is
+/// exact, in particular the result is zero exactly when the points are collinear.
+pub fn orient2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    let left = (b[0] - a[0]) * (c[1] - a[1]);
+    let right = (b[1] - a[1]) * (c[0] - a[0]);
+    let det = left - right;
+    if det.abs() > ORIENT2D_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+    let left = product(&difference(b[0], a[0]), &difference(c[1], a[1]));
+    let right = product(&difference(b[1], a[1]), &difference(c[0], a[0]));
+    approximate(&sum(&left, &negate(&right)))
+}
+
+/// Six times the signed volume of the tetrahedron abcd, positive if d lies on the side of abc its
+/// counter-clockwise normal points to. The sign is exact, in particular the result is zero
+/// exactly when the points are coplanar.
+pub fn orient3d(a: [f64; 3], b: [f64; 3], c: [f64; 3], d: [f64; 3]) -> f64 {
+    let [u, v, w] = [b, c, d].map(|p| [p[0] - a[0], p[1] - a[1], p[2] - a[2]]);
+    let minors = [(1, 2), (0, 2), (0, 1)].map(|(i, j)| (v[i] * w[j], v[j] * w[i]));
+    let det = u[0] * (minors[0].0 - minors[0].1) - u[1] * (minors[1].0 - minors[1].1)
+        + u[2] * (minors[2].0 - minors[2].1);
+    let permanent = (0..3)
+        .map(|k| u[k].abs() * (minors[k].0.abs() + minors[k].1.abs()))
+        .sum::<f64>();
+    if det.abs() > ORIENT3D_BOUND * permanent {
+        return det;
+    }
+    let [u, v, w] = [b, c, d].map(|p| [0, 1, 2].map(|k| difference(p[k], a[k])));
+    let terms = [(0, 1, 2), (1, 2, 0), (2, 0, 1)].map(|(i, j, k)| {
+        let minor = sum(&product(&v[j], &w[k]), &negate(&product(&v[k], &w[j])));
+        product(&u[i], &minor)
+    });
+    approximate(&sum(&sum(&terms[0], &terms[1]), &terms[2]))
+}
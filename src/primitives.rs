@@ -0,0 +1,265 @@
+use crate::types::{IndexedMesh, IndexedTriangle, Vertex};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+// Builds a mesh from vertices and faces, computing the normals.
+pub(crate) fn mesh_from_faces(vertices: Vec<Vertex>, faces: Vec<[usize; 3]>) -> IndexedMesh {
+    let faces = faces
+        .into_iter()
+        .map(|[a, b, c]| IndexedTriangle {
+            normal: super::utils::normal(vertices[a], vertices[b], vertices[c]),
+            vertices: [a, b, c],
+        })
+        .collect();
+    IndexedMesh { vertices, faces }
+}
+
+// Point on a horizontal circle around the z axis.
+fn circle_point(radius: f32, angle: f32, z: f32) -> Vertex {
+    Vertex::new([radius * angle.cos(), radius * angle.sin(), z])
+}
+
+/// Generators for simple closed shapes. Apart from [plane](#method.plane), they are watertight
+/// with outward facing triangles, i.e. they pass [validate](#method.validate), and have a
+/// positive volume. Resolutions below the minimum of a shape are raised to it.
+impl IndexedMesh {
+    /// Creates a cube with edge length `size`, centered at the origin.
+    ///
+    /// ```
+    /// let cube = stl_io::IndexedMesh::cube(2.0);
+    /// assert_eq!(cube.faces.len(), 12);
+    /// assert!(cube.validate().is_ok());
+    /// ```
+    pub fn cube(size: f32) -> IndexedMesh {
+        let half = size / 2.0;
+        IndexedMesh::cuboid(Vertex::new([-half; 3]), Vertex::new([half; 3]))
+    }
+
+    /// Creates an axis aligned box from `min` to `max`.
+    pub fn cuboid(min: Vertex, max: Vertex) -> IndexedMesh {
+        let vertices = (0..8)
+            .map(|i| {
+                Vertex::new([0, 1, 2].map(|c| if i & (1 << c) == 0 { min[c] } else { max[c] }))
+            })
+            .collect();
+        mesh_from_faces(
+            vertices,
+            vec![
+                [0, 2, 3],
+                [0, 3, 1],
+                [4, 5, 7],
+                [4, 7, 6],
+                [0, 1, 5],
+                [0, 5, 4],
+                [2, 6, 7],
+                [2, 7, 3],
+                [0, 4, 6],
+                [0, 6, 2],
+                [1, 3, 7],
+                [1, 7, 5],
+            ],
+        )
+    }
+
+    /// Creates a sphere centered at the origin from `segments` meridians (at least 3) and
+    /// `rings` bands of latitude (at least 2), with its poles on the z axis.
+    pub fn uv_sphere(radius: f32, segments: usize, rings: usize) -> IndexedMesh {
+        let (segments, rings) = (segments.max(3), rings.max(2));
+        let mut vertices = vec![Vertex::new([0.0, 0.0, radius])];
+        for r in 1..rings {
+            let theta = PI * r as f32 / rings as f32;
+            for s in 0..segments {
+                let phi = 2.0 * PI * s as f32 / segments as f32;
+                vertices.push(circle_point(
+                    radius * theta.sin(),
+                    phi,
+                    radius * theta.cos(),
+                ));
+            }
+        }
+        vertices.push(Vertex::new([0.0, 0.0, -radius]));
+        let south = vertices.len() - 1;
+        let ring = |r: usize, s: usize| 1 + (r - 1) * segments + s % segments;
+
+        let mut faces = Vec::new();
+        for s in 0..segments {
+            faces.push([0, ring(1, s), ring(1, s + 1)]);
+            for r in 1..rings - 1 {
+                faces.push([ring(r, s), ring(r + 1, s), ring(r + 1, s + 1)]);
+                faces.push([ring(r, s), ring(r + 1, s + 1), ring(r, s + 1)]);
+            }
+            faces.push([south, ring(rings - 1, s + 1), ring(rings - 1, s)]);
+        }
+        mesh_from_faces(vertices, faces)
+    }
+
+    /// Creates a sphere centered at the origin by splitting every face of an icosahedron into
+    /// four, `subdivisions` times, giving `20 * 4^subdivisions` evenly sized faces.
+    pub fn ico_sphere(radius: f32, subdivisions: usize) -> IndexedMesh {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut vertices = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .map(Vertex::new)
+        .to_vec();
+        let mut faces = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let (pa, pb) = (vertices[a], vertices[b]);
+                    vertices.push(Vertex::new([0, 1, 2].map(|c| (pa[c] + pb[c]) / 2.0)));
+                    vertices.len() - 1
+                })
+            };
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+        for v in &mut vertices {
+            let l = super::utils::length(*v);
+            *v = Vertex::new(v.0.map(|c| c * radius / l));
+        }
+        mesh_from_faces(vertices, faces)
+    }
+
+    /// Creates a cylinder around the z axis, from `z = 0` to `z = height`, approximating its
+    /// circles with `segments` points (at least 3).
+    pub fn cylinder(radius: f32, height: f32, segments: usize) -> IndexedMesh {
+        let segments = segments.max(3);
+        let mut vertices = vec![Vertex::new([0.0; 3]), Vertex::new([0.0, 0.0, height])];
+        for s in 0..segments {
+            let phi = 2.0 * PI * s as f32 / segments as f32;
+            vertices.push(circle_point(radius, phi, 0.0));
+            vertices.push(circle_point(radius, phi, height));
+        }
+        let bottom = |s: usize| 2 + 2 * (s % segments);
+        let top = |s: usize| 3 + 2 * (s % segments);
+
+        let mut faces = Vec::new();
+        for s in 0..segments {
+            faces.push([0, bottom(s + 1), bottom(s)]);
+            faces.push([bottom(s), bottom(s + 1), top(s + 1)]);
+            faces.push([bottom(s), top(s + 1), top(s)]);
+            faces.push([1, top(s), top(s + 1)]);
+        }
+        mesh_from_faces(vertices, faces)
+    }
+
+    /// Creates a cone around the z axis with its base at `z = 0` and its tip at `z = height`,
+    /// approximating the base circle with `segments` points (at least 3).
+    pub fn cone(radius: f32, height: f32, segments: usize) -> IndexedMesh {
+        let segments = segments.max(3);
+        let mut vertices = vec![Vertex::new([0.0; 3]), Vertex::new([0.0, 0.0, height])];
+        for s in 0..segments {
+            let phi = 2.0 * PI * s as f32 / segments as f32;
+            vertices.push(circle_point(radius, phi, 0.0));
+        }
+        let base = |s: usize| 2 + s % segments;
+
+        let mut faces = Vec::new();
+        for s in 0..segments {
+            faces.push([0, base(s + 1), base(s)]);
+            faces.push([base(s), base(s + 1), 1]);
+        }
+        mesh_from_faces(vertices, faces)
+    }
+
+    /// Creates a torus around the z axis, centered at the origin. The tube of radius
+    /// `minor_radius` runs along a circle of radius `major_radius`, which is approximated with
+    /// `major_segments` points, the tube's cross-section with `minor_segments` points (each at
+    /// least 3).
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: usize,
+        minor_segments: usize,
+    ) -> IndexedMesh {
+        let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+        let mut vertices = Vec::with_capacity(major_segments * minor_segments);
+        for i in 0..major_segments {
+            let u = 2.0 * PI * i as f32 / major_segments as f32;
+            for j in 0..minor_segments {
+                let v = 2.0 * PI * j as f32 / minor_segments as f32;
+                vertices.push(circle_point(
+                    major_radius + minor_radius * v.cos(),
+                    u,
+                    minor_radius * v.sin(),
+                ));
+            }
+        }
+        let point = |i: usize, j: usize| (i % major_segments) * minor_segments + j % minor_segments;
+
+        let mut faces = Vec::new();
+        for i in 0..major_segments {
+            for j in 0..minor_segments {
+                faces.push([point(i, j), point(i + 1, j), point(i + 1, j + 1)]);
+                faces.push([point(i, j), point(i + 1, j + 1), point(i, j + 1)]);
+            }
+        }
+        mesh_from_faces(vertices, faces)
+    }
+
+    /// Creates a flat rectangle of `width` along x and `depth` along y in the plane `z = 0`,
+    /// centered at the origin and facing up, split into `subdivisions` (at least 1) squares
+    /// along each side. Unlike the other shapes it is not closed.
+    pub fn plane(width: f32, depth: f32, subdivisions: usize) -> IndexedMesh {
+        let n = subdivisions.max(1);
+        let mut vertices = Vec::with_capacity((n + 1) * (n + 1));
+        for j in 0..=n {
+            for i in 0..=n {
+                vertices.push(Vertex::new([
+                    width * (i as f32 / n as f32 - 0.5),
+                    depth * (j as f32 / n as f32 - 0.5),
+                    0.0,
+                ]));
+            }
+        }
+        let point = |i: usize, j: usize| j * (n + 1) + i;
+
+        let mut faces = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                faces.push([point(i, j), point(i + 1, j), point(i + 1, j + 1)]);
+                faces.push([point(i, j), point(i + 1, j + 1), point(i, j + 1)]);
+            }
+        }
+        mesh_from_faces(vertices, faces)
+    }
+}
@@ -0,0 +1,323 @@
+use crate::types::{IndexedMesh, Normal, Vertex};
+use std::collections::{HashMap, HashSet};
+use std::io::Result;
+
+/// Plane of all points `p` with `dot(normal, p) == offset`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// Unit normal of the plane, pointing to the side considered above the plane.
+    pub normal: Normal,
+    /// Signed distance of the plane from the origin along the normal.
+    pub offset: f32,
+}
+
+impl Plane {
+    /// Creates a plane from a normal, which is normalized, and a point on the plane.
+    pub fn new(normal: Normal, point: Vertex) -> Plane {
+        let l = super::utils::length(normal);
+        let normal = Normal::new(normal.0.map(|c| c / l));
+        Plane {
+            normal,
+            offset: super::utils::dot(normal, point),
+        }
+    }
+
+    /// Creates the horizontal plane at height `z`, with its normal pointing up.
+    pub fn horizontal(z: f32) -> Plane {
+        Plane {
+            normal: Normal::new([0.0, 0.0, 1.0]),
+            offset: z,
+        }
+    }
+
+    /// Signed distance of a point to the plane, positive above the plane.
+    pub fn signed_distance(&self, point: Vertex) -> f32 {
+        self.distance(point) as f32
+    }
+
+    pub(crate) fn distance(&self, point: Vertex) -> f64 {
+        (0..3)
+            .map(|i| f64::from(self.normal[i]) * f64::from(point[i]))
+            .sum::<f64>()
+            - f64::from(self.offset)
+    }
+
+    /// Two orthonormal directions `(u, v)` within the plane, such that `u`, `v` and the normal
+    /// form a right-handed coordinate system. For horizontal planes they are the x and y axis.
+    pub fn basis(&self) -> (Vertex, Vertex) {
+        use super::utils::{cross, length};
+        let n = self.normal;
+        let helper = if n[0].abs() < 0.9 {
+            Vertex::new([1.0, 0.0, 0.0])
+        } else {
+            Vertex::new([0.0, 1.0, 0.0])
+        };
+        let v = cross(n, helper);
+        let l = length(v);
+        let v = Vertex::new(v.0.map(|c| c / l));
+        (cross(v, n), v)
+    }
+
+    /// Projects a point into the 2D coordinate system given by [basis](#method.basis).
+    pub fn project(&self, point: Vertex) -> [f32; 2] {
+        let (u, v) = self.basis();
+        [super::utils::dot(u, point), super::utils::dot(v, point)]
+    }
+}
+
+/// Polyline where a mesh intersects a [Plane](struct.Plane.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    /// Points of the polyline. For closed contours the last point connects to the first one.
+    pub points: Vec<Vertex>,
+    /// Whether the contour is a closed loop. Only open meshes produce open contours.
+    pub closed: bool,
+    /// Whether the contour is the boundary of a hole, i.e. runs clockwise when seen from above
+    /// the plane, with the material on its left. Outer boundaries run counter-clockwise.
+    pub is_hole: bool,
+    /// Index of the innermost contour of the same slice that encloses this one.
+    pub parent: Option<usize>,
+}
+
+/// Contours of a mesh at one height, see
+/// [IndexedMesh::slice_layers](struct.IndexedMesh.html#method.slice_layers).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    /// Height of the slicing plane.
+    pub z: f32,
+    /// Contours of the mesh at this height.
+    pub contours: Vec<Contour>,
+}
+
+// Identifies an intersection point by the edge it lies on.
+pub(crate) type EdgeKey = (usize, usize);
+
+fn edge_key(a: usize, b: usize) -> EdgeKey {
+    (a.min(b), a.max(b))
+}
+
+impl IndexedMesh {
+    /// Intersects the mesh with a plane and returns the resulting contours.
+    ///
+    /// Intersection points are identified by the mesh edge they lie on, so contours are stitched
+    /// using the shared vertices of the mesh rather than by comparing coordinates. Vertices
+    /// exactly on the plane are treated as lying slightly above it: a plane touching the top of
+    /// a part yields its outline, a plane touching its bottom yields nothing.
+    /// The contours follow the orientation of the faces, which should point outward (see
+    /// [orient_faces](#method.orient_faces)) for [is_hole](struct.Contour.html#structfield.is_hole)
+    /// to be meaningful.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Plane, Vertex};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let tetrahedron = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([1.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 1.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 1.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// let contours = tetrahedron.slice(&Plane::horizontal(0.5));
+    /// assert_eq!(contours.len(), 1);
+    /// assert_eq!(contours[0].points.len(), 3);
+    /// assert!(contours[0].closed && !contours[0].is_hole);
+    /// ```
+    pub fn slice(&self, plane: &Plane) -> Vec<Contour> {
+        let intersection = PlaneIntersection::new(self, plane);
+        let mut contours = Vec::new();
+        for (keys, closed) in &intersection.chains {
+            let mut points: Vec<Vertex> = Vec::with_capacity(keys.len());
+            for &key in keys {
+                let point = intersection.point(self, key);
+                // Vertices on the plane are hit by several edges.
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+            }
+            if *closed && points.len() > 1 && points[0] == points[points.len() - 1] {
+                points.pop();
+            }
+            if points.len() < 2 || (*closed && points.len() < 3) {
+                continue;
+            }
+            contours.push(Contour {
+                points,
+                closed: *closed,
+                is_hole: false,
+                parent: None,
+            });
+        }
+        assign_nesting(plane, &mut contours);
+        contours
+    }
+
+    /// Slices the mesh at the given heights, see [slice](#method.slice).
+    pub fn slice_at_heights(&self, heights: &[f32]) -> Vec<Layer> {
+        heights
+            .iter()
+            .map(|&z| Layer {
+                z,
+                contours: self.slice(&Plane::horizontal(z)),
+            })
+            .collect()
+    }
+
+    /// Slices the mesh into horizontal layers of `layer_height`, cutting every layer in its
+    /// middle, as a 3D printer would. The first layer starts at the bottom of the mesh.
+    /// Fails with `InvalidInput` if `layer_height` is not positive and finite, or so small that
+    /// the layers cannot be counted.
+    pub fn slice_layers(&self, layer_height: f32) -> Result<Vec<Layer>> {
+        if !(layer_height.is_finite() && layer_height > 0.0) {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!(
+                    "layer height must be positive and finite, got {}",
+                    layer_height
+                ),
+            ));
+        }
+        let (min, max) = match self.bounding_box() {
+            Some(bounds) => bounds,
+            None => return Ok(Vec::new()),
+        };
+        let count = ((max[2] - min[2]) / layer_height).ceil().max(0.0);
+        // NaN comes from non-finite vertices.
+        if count.is_nan() || count >= usize::MAX as f32 {
+            return Err(::std::io::Error::new(
+                ::std::io::ErrorKind::InvalidInput,
+                format!(
+                    "too many layers of height {} to cover the mesh",
+                    layer_height
+                ),
+            ));
+        }
+        let heights = (0..count as usize)
+            .map(|i| min[2] + (i as f32 + 0.5) * layer_height)
+            .collect::<Vec<_>>();
+        Ok(self.slice_at_heights(&heights))
+    }
+}
+
+// Where a mesh crosses a plane, as chains of the mesh edges crossing it.
+pub(crate) struct PlaneIntersection {
+    // Signed distance of every vertex to the plane.
+    pub distances: Vec<f64>,
+    // Chains of crossing edges and whether they are closed, oriented like contours.
+    pub chains: Vec<(Vec<EdgeKey>, bool)>,
+}
+
+impl PlaneIntersection {
+    pub fn new(mesh: &IndexedMesh, plane: &Plane) -> PlaneIntersection {
+        let distances = mesh
+            .vertices
+            .iter()
+            .map(|&v| plane.distance(v))
+            .collect::<Vec<_>>();
+        let above = |vi: usize| distances[vi] >= 0.0;
+
+        // Every face crossing the plane contributes a segment from the edge where it leaves the
+        // upper half space to the edge where it enters it again.
+        let mut segments: HashMap<EdgeKey, Vec<EdgeKey>> = HashMap::new();
+        for face in &mesh.faces {
+            let mut start = None;
+            let mut end = None;
+            for i in 0..3 {
+                let a = face.vertices[i];
+                let b = face.vertices[(i + 1) % 3];
+                if above(a) == above(b) {
+                    continue;
+                }
+                if above(a) {
+                    start = Some(edge_key(a, b));
+                } else {
+                    end = Some(edge_key(a, b));
+                }
+            }
+            if let (Some(start), Some(end)) = (start, end) {
+                segments.entry(start).or_default().push(end);
+            }
+        }
+
+        // Open polylines have to be walked from their start, closed ones can start anywhere.
+        let ends = segments.values().flatten().copied().collect::<HashSet<_>>();
+        let mut starts = segments.keys().copied().collect::<Vec<_>>();
+        starts.sort_unstable();
+        starts.sort_by_key(|key| ends.contains(key));
+
+        let mut chains = Vec::new();
+        for first in starts {
+            while let Some(mut next) = segments.get_mut(&first).and_then(|s| s.pop()) {
+                let mut keys = vec![first];
+                let closed = loop {
+                    if next == first {
+                        break true;
+                    }
+                    keys.push(next);
+                    match segments.get_mut(&next).and_then(|s| s.pop()) {
+                        Some(n) => next = n,
+                        None => break false,
+                    }
+                };
+                chains.push((keys, closed));
+            }
+        }
+        PlaneIntersection { distances, chains }
+    }
+
+    // The vertex on the plane for an edge crossing it, if the edge ends on the plane.
+    pub fn vertex_on_plane(&self, key: EdgeKey) -> Option<usize> {
+        [key.0, key.1]
+            .into_iter()
+            .find(|&v| self.distances[v] == 0.0)
+    }
+
+    // Where an edge crosses the plane.
+    pub fn point(&self, mesh: &IndexedMesh, key: EdgeKey) -> Vertex {
+        if let Some(v) = self.vertex_on_plane(key) {
+            return mesh.vertices[v];
+        }
+        let (a, b) = key;
+        let t = self.distances[a] / (self.distances[a] - self.distances[b]);
+        let (pa, pb) = (mesh.vertices[a], mesh.vertices[b]);
+        Vertex::new(
+            [0, 1, 2]
+                .map(|c| (f64::from(pa[c]) + t * (f64::from(pb[c]) - f64::from(pa[c]))) as f32),
+        )
+    }
+}
+
+// Determines which closed contours are holes and which contour encloses which.
+pub(crate) fn assign_nesting(plane: &Plane, contours: &mut [Contour]) {
+    let projected = contours
+        .iter()
+        .map(|c| {
+            c.points
+                .iter()
+                .map(|&p| plane.project(p))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let areas = projected
+        .iter()
+        .zip(contours.iter())
+        .map(|(polygon, contour)| {
+            if contour.closed {
+                super::utils::polygon_area(polygon)
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<_>>();
+    for i in 0..contours.len() {
+        contours[i].is_hole = areas[i] < 0.0;
+        contours[i].parent = (0..contours.len())
+            .filter(|&j| {
+                j != i
+                    && areas[j].abs() > areas[i].abs()
+                    && super::utils::point_in_polygon(projected[i][0], &projected[j])
+            })
+            .min_by(|&a, &b| areas[a].abs().total_cmp(&areas[b].abs()));
+    }
+}
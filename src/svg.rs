@@ -0,0 +1,120 @@
+use crate::slice::Layer;
+use crate::types::Vertex;
+use std::io::{BufWriter, Result, Write};
+
+/// Writes a single [Layer](struct.Layer.html) as SVG document.
+///
+/// `bounds` is the area to show, usually the bounding box of the sliced mesh, so that the
+/// documents of all layers line up. Only x and y are used, the z coordinates are ignored.
+/// Coordinates are written as they are, but the document size is given in millimeters, which
+/// is what resin printers expect (see [convert_units](struct.IndexedMesh.html#method.convert_units)).
+/// The closed contours of the layer form a single path with even-odd fill, so holes stay
+/// empty. Open contours are drawn as red lines. Fails with `InvalidInput` if the bounds have no
+/// positive, finite width and height.
+///
+/// ```
+/// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+/// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+/// let tetrahedron = IndexedMesh {
+///     vertices: vec![
+///         Vertex::new([0.0, 0.0, 0.0]),
+///         Vertex::new([1.0, 0.0, 0.0]),
+///         Vertex::new([0.0, 1.0, 0.0]),
+///         Vertex::new([0.0, 0.0, 1.0]),
+///     ],
+///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+/// };
+/// let bounds = tetrahedron.bounding_box().unwrap();
+/// let layers = tetrahedron.slice_layers(0.25).unwrap();
+/// assert_eq!(layers.len(), 4);
+/// let mut svg = Vec::<u8>::new();
+/// stl_io::write_svg_layer(&mut svg, &layers[0], bounds).unwrap();
+/// let svg = String::from_utf8(svg).unwrap();
+/// assert!(svg.contains(r#"width="1mm" height="1mm" viewBox="0 -1 1 1""#));
+/// assert!(svg.contains(r#"<path d="M"#));
+/// ```
+pub fn write_svg_layer<W: Write>(
+    writer: &mut W,
+    layer: &Layer,
+    bounds: (Vertex, Vertex),
+) -> Result<()> {
+    write_svg_layers(writer, ::std::slice::from_ref(layer), bounds)
+}
+
+/// Writes several [Layers](struct.Layer.html) into one SVG document, see
+/// [write_svg_layer](fn.write_svg_layer.html). Every layer becomes a group with the id
+/// `layer-<index>` and its height in the `data-z` attribute.
+pub fn write_svg_layers<W: Write>(
+    writer: &mut W,
+    layers: &[Layer],
+    bounds: (Vertex, Vertex),
+) -> Result<()> {
+    let (min, max) = bounds;
+    let (width, height) = (max[0] - min[0], max[1] - min[1]);
+    if !(width.is_finite() && width > 0.0 && height.is_finite() && height > 0.0) {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!(
+                "SVG bounds must have a positive size, got {} x {}",
+                width, height
+            ),
+        ));
+    }
+    let mut writer = BufWriter::new(writer);
+    writeln!(
+        writer,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>"
+    )?;
+    // The y axis of SVG points down, so the groups are mirrored and the view box is moved.
+    writeln!(
+        writer,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}mm\" height=\"{}mm\" viewBox=\"{} {} {} {}\">",
+        width, height, min[0], -max[1], width, height
+    )?;
+    for (i, layer) in layers.iter().enumerate() {
+        writeln!(
+            writer,
+            "  <g id=\"layer-{}\" data-z=\"{}\" transform=\"scale(1,-1)\">",
+            i, layer.z
+        )?;
+        let mut filled = String::new();
+        for contour in layer.contours.iter().filter(|c| c.closed) {
+            if !filled.is_empty() {
+                filled.push(' ');
+            }
+            filled.push_str(&path_data(&contour.points));
+            filled.push('Z');
+        }
+        if !filled.is_empty() {
+            writeln!(
+                writer,
+                "    <path d=\"{}\" fill=\"black\" fill-rule=\"evenodd\"/>",
+                filled
+            )?;
+        }
+        for contour in layer.contours.iter().filter(|c| !c.closed) {
+            writeln!(
+                writer,
+                "    <path d=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"0.1\"/>",
+                path_data(&contour.points)
+            )?;
+        }
+        writeln!(writer, "  </g>")?;
+    }
+    writeln!(writer, "</svg>")?;
+    writer.flush()
+}
+
+// Path data for a polyline, without closing it.
+fn path_data(points: &[Vertex]) -> String {
+    let mut data = String::new();
+    for (i, p) in points.iter().enumerate() {
+        data.push_str(&format!(
+            "{}{} {}",
+            if i == 0 { 'M' } else { 'L' },
+            p[0],
+            p[1]
+        ));
+    }
+    data
+}
@@ -0,0 +1,435 @@
+use crate::transform::Transform;
+use crate::types::{IndexedMesh, Vertex};
+use crate::units::Units;
+use crate::xml::{escape, XmlReader};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Result, Seek, Write};
+
+const MODEL_PATH: &str = "3D/3dmodel.model";
+const MODEL_RELATIONSHIP: &str = "http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel";
+const CORE_NAMESPACE: &str = "http://schemas.microsoft.com/3dmanufacturing/core/2015/02";
+
+// Nesting depth of components beyond which a file is rejected, which bounds the recursion.
+const MAX_COMPONENT_DEPTH: usize = 64;
+
+// Limit on the vertices, and separately the triangles, that copying objects into the meshes of
+// others and of build items may produce. Components can reference the same object over and over,
+// at every level, so a small file could otherwise expand into billions of triangles.
+const MAX_COPIED: usize = 1 << 24;
+
+// Unit names of the model element, as defined by the 3MF core spec.
+const UNIT_NAMES: &[(Units, &str)] = &[
+    (Units::Micrometers, "micron"),
+    (Units::Millimeters, "millimeter"),
+    (Units::Centimeters, "centimeter"),
+    (Units::Inches, "inch"),
+    (Units::Feet, "foot"),
+    (Units::Meters, "meter"),
+];
+
+/// Object placed on the build plate of a 3MF file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreeMfItem {
+    /// Name of the object, empty if the file does not give one.
+    pub name: String,
+    /// The object's mesh, with the transforms of its components applied, but not the one of
+    /// the item.
+    pub mesh: IndexedMesh,
+    /// Placement of the mesh on the build plate.
+    pub transform: Transform,
+}
+
+/// Contents of a 3MF file, see [read_3mf](fn.read_3mf.html) and
+/// [write_3mf](fn.write_3mf.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThreeMfModel {
+    /// Unit of all coordinates.
+    pub units: Units,
+    /// The build items, in the order of the file.
+    pub items: Vec<ThreeMfItem>,
+}
+
+impl ThreeMfModel {
+    /// Creates a model with `mesh` as its only build item, placed as is.
+    pub fn from_mesh(mesh: IndexedMesh, units: Units) -> ThreeMfModel {
+        ThreeMfModel {
+            units,
+            items: vec![ThreeMfItem {
+                name: String::new(),
+                mesh,
+                transform: Transform::IDENTITY,
+            }],
+        }
+    }
+
+    /// Places all items on the build plate and merges them into one mesh.
+    pub fn to_mesh(&self) -> IndexedMesh {
+        let placed = self
+            .items
+            .iter()
+            .map(|item| {
+                let mut mesh = item.mesh.clone();
+                mesh.transform(&item.transform);
+                mesh
+            })
+            .collect::<Vec<_>>();
+        IndexedMesh::merge(&placed, None)
+    }
+}
+
+fn invalid(message: String) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("3MF: {}", message),
+    )
+}
+
+// 3MF matrices have 4 rows and 3 columns and are applied to row vectors, so the values are
+// m00 m01 m02 m10 ... m32 with the translation in the last row.
+fn parse_transform(text: &str) -> Result<Transform> {
+    let values = text
+        .split_whitespace()
+        .map(|v| v.parse::<f32>())
+        .collect::<::std::result::Result<Vec<_>, _>>()
+        .ok()
+        .filter(|v| v.len() == 12)
+        .ok_or_else(|| invalid(format!("invalid transform {:?}", text)))?;
+    let mut matrix = Transform::IDENTITY.matrix();
+    for (r, row) in matrix.iter_mut().take(3).enumerate() {
+        for (c, m) in row.iter_mut().enumerate() {
+            *m = values[c * 3 + r];
+        }
+    }
+    Ok(Transform::from_matrix(matrix))
+}
+
+fn format_transform(transform: &Transform) -> String {
+    let matrix = transform.matrix();
+    (0..4)
+        .flat_map(|c| (0..3).map(move |r| matrix[r][c].to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Resource of the model part, either a mesh or an assembly of other objects.
+#[derive(Default)]
+struct Object {
+    name: String,
+    vertices: Vec<Vertex>,
+    triangles: Vec<[usize; 3]>,
+    components: Vec<(usize, Transform)>,
+}
+
+// Flattens objects into meshes, each object only once however often it is referenced.
+struct Resolver<'a> {
+    objects: &'a HashMap<usize, Object>,
+    resolved: HashMap<usize, IndexedMesh>,
+    // Objects whose components are being resolved, to detect cycles.
+    visiting: HashSet<usize>,
+    copied: [usize; 2],
+}
+
+impl<'a> Resolver<'a> {
+    fn new(objects: &'a HashMap<usize, Object>) -> Self {
+        Resolver {
+            objects,
+            resolved: HashMap::new(),
+            visiting: HashSet::new(),
+            copied: [0, 0],
+        }
+    }
+
+    // Accounts for a copy of a resolved object, failing once too much has been copied.
+    fn copy(&mut self, id: usize) -> Result<IndexedMesh> {
+        let mesh = &self.resolved[&id];
+        let sizes = [mesh.vertices.len(), mesh.faces.len()];
+        for (copied, size) in self.copied.iter_mut().zip(sizes) {
+            *copied = copied.saturating_add(size);
+            if *copied > MAX_COPIED {
+                return Err(invalid(format!(
+                    "object {} is referenced too often, flattening the components would \
+                     exceed {} vertices or triangles",
+                    id, MAX_COPIED
+                )));
+            }
+        }
+        Ok(mesh.clone())
+    }
+
+    fn resolve(&mut self, id: usize) -> Result<()> {
+        if self.resolved.contains_key(&id) {
+            return Ok(());
+        }
+        if self.visiting.contains(&id) {
+            return Err(invalid(format!("object {} contains itself", id)));
+        }
+        if self.visiting.len() >= MAX_COMPONENT_DEPTH {
+            return Err(invalid(format!(
+                "components of object {} nest too deep",
+                id
+            )));
+        }
+        let object = self
+            .objects
+            .get(&id)
+            .ok_or_else(|| invalid(format!("reference to unknown object {}", id)))?;
+        if let Some(t) = object
+            .triangles
+            .iter()
+            .find(|t| t.iter().any(|&v| v >= object.vertices.len()))
+        {
+            return Err(invalid(format!(
+                "triangle {:?} of object {} out of range of its {} vertices",
+                t,
+                id,
+                object.vertices.len()
+            )));
+        }
+        self.visiting.insert(id);
+        let mut parts = vec![super::primitives::mesh_from_faces(
+            object.vertices.clone(),
+            object.triangles.clone(),
+        )];
+        for (component, transform) in &object.components {
+            self.resolve(*component)?;
+            let mut mesh = self.copy(*component)?;
+            mesh.transform(transform);
+            parts.push(mesh);
+        }
+        self.visiting.remove(&id);
+        self.resolved.insert(id, IndexedMesh::merge(&parts, None));
+        Ok(())
+    }
+}
+
+fn parse_model(xml: &str) -> Result<ThreeMfModel> {
+    let mut units = Units::Millimeters;
+    let mut objects = HashMap::new();
+    let mut build = Vec::new();
+    let mut current: Option<(usize, Object)> = None;
+    let mut reader = XmlReader::new(xml);
+    while let Some(element) = reader.next_element()? {
+        if element.end {
+            if element.name == "object" {
+                if let Some((id, object)) = current.take() {
+                    objects.insert(id, object);
+                }
+            }
+            continue;
+        }
+        match (element.name, current.as_mut()) {
+            ("model", _) => {
+                if let Some(unit) = element.attribute("unit") {
+                    units = UNIT_NAMES
+                        .iter()
+                        .find(|(_, name)| *name == unit)
+                        .map(|(u, _)| *u)
+                        .ok_or_else(|| invalid(format!("unknown unit {:?}", unit)))?;
+                }
+            }
+            ("object", _) => {
+                let object = Object {
+                    name: element.attribute("name").unwrap_or_default().to_string(),
+                    ..Object::default()
+                };
+                let id = element.required("id")?;
+                if element.empty {
+                    objects.insert(id, object);
+                } else {
+                    current = Some((id, object));
+                }
+            }
+            ("vertex", Some((_, object))) => {
+                object.vertices.push(Vertex::new([
+                    element.required("x")?,
+                    element.required("y")?,
+                    element.required("z")?,
+                ]));
+            }
+            ("triangle", Some((_, object))) => {
+                object.triangles.push([
+                    element.required("v1")?,
+                    element.required("v2")?,
+                    element.required("v3")?,
+                ]);
+            }
+            ("component", Some((_, object))) => {
+                let transform = match element.attribute("transform") {
+                    Some(t) => parse_transform(t)?,
+                    None => Transform::IDENTITY,
+                };
+                object
+                    .components
+                    .push((element.required("objectid")?, transform));
+            }
+            ("item", _) => {
+                let transform = match element.attribute("transform") {
+                    Some(t) => parse_transform(t)?,
+                    None => Transform::IDENTITY,
+                };
+                build.push((element.required::<usize>("objectid")?, transform));
+            }
+            _ => {}
+        }
+    }
+
+    let mut resolver = Resolver::new(&objects);
+    let mut placed = HashSet::new();
+    let mut items = Vec::with_capacity(build.len());
+    for (id, transform) in build {
+        resolver.resolve(id)?;
+        // The first item of an object gets it as stored, only more items make copies.
+        let mesh = if placed.insert(id) {
+            resolver.resolved[&id].clone()
+        } else {
+            resolver.copy(id)?
+        };
+        items.push(ThreeMfItem {
+            name: objects.get(&id).map(|o| o.name.clone()).unwrap_or_default(),
+            mesh,
+            transform,
+        });
+    }
+    Ok(ThreeMfModel { units, items })
+}
+
+// Path of the model part, as given by the package relationships.
+fn model_path(rels: &str) -> Result<Option<String>> {
+    let mut reader = XmlReader::new(rels);
+    while let Some(element) = reader.next_element()? {
+        if element.name == "Relationship" && element.attribute("Type") == Some(MODEL_RELATIONSHIP) {
+            if let Some(target) = element.attribute("Target") {
+                return Ok(Some(target.trim_start_matches('/').to_string()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn read_part<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, path: &str) -> Result<String> {
+    let mut text = String::new();
+    archive.by_name(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Reads the build items of a 3MF file, following the core specification.
+///
+/// The model part is found through the package relationships. Objects made of components are
+/// flattened into a single mesh per build item. Normals are computed from the triangles, since
+/// 3MF does not store them. Materials, colors and extensions are ignored. Files whose components
+/// reference themselves, nest more than 64 levels deep or flatten to more than 16 million copied
+/// vertices or triangles are rejected as invalid.
+///
+/// ```
+/// use stl_io::{IndexedMesh, ThreeMfModel, Units};
+/// let model = ThreeMfModel::from_mesh(IndexedMesh::cube(10.0), Units::Millimeters);
+/// let mut file = ::std::io::Cursor::new(Vec::new());
+/// stl_io::write_3mf(&mut file, &model).unwrap();
+/// let read = stl_io::read_3mf(&mut file).unwrap();
+/// assert_eq!(read, model);
+/// ```
+pub fn read_3mf<R: Read + Seek>(read: &mut R) -> Result<ThreeMfModel> {
+    let mut archive = zip::ZipArchive::new(read)?;
+    let path = match read_part(&mut archive, "_rels/.rels") {
+        Ok(rels) => model_path(&rels)?,
+        Err(_) => None,
+    };
+    let model = read_part(&mut archive, path.as_deref().unwrap_or(MODEL_PATH))?;
+    parse_model(&model)
+}
+
+/// Writes a 3MF file with one mesh object and build item for every item of `model`.
+pub fn write_3mf<W: Write + Seek>(writer: &mut W, model: &ThreeMfModel) -> Result<()> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut archive = zip::ZipWriter::new(writer);
+
+    archive.start_file("[Content_Types].xml", options)?;
+    writeln!(archive, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        archive,
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#
+    )?;
+    writeln!(
+        archive,
+        r#" <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#
+    )?;
+    writeln!(
+        archive,
+        r#" <Default Extension="model" ContentType="application/vnd.ms-package.3dmanufacturing-3dmodel+xml"/>"#
+    )?;
+    writeln!(archive, "</Types>")?;
+
+    archive.start_file("_rels/.rels", options)?;
+    writeln!(archive, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        archive,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#
+    )?;
+    writeln!(
+        archive,
+        r#" <Relationship Target="/{}" Id="rel0" Type="{}"/>"#,
+        MODEL_PATH, MODEL_RELATIONSHIP
+    )?;
+    writeln!(archive, "</Relationships>")?;
+
+    archive.start_file(MODEL_PATH, options)?;
+    let unit = UNIT_NAMES
+        .iter()
+        .find(|(u, _)| *u == model.units)
+        .map(|(_, name)| *name)
+        .unwrap_or("millimeter");
+    writeln!(archive, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        archive,
+        r#"<model unit="{}" xml:lang="en-US" xmlns="{}">"#,
+        unit, CORE_NAMESPACE
+    )?;
+    writeln!(archive, " <resources>")?;
+    for (i, item) in model.items.iter().enumerate() {
+        write!(archive, r#"  <object id="{}" type="model""#, i + 1)?;
+        if !item.name.is_empty() {
+            write!(archive, r#" name="{}""#, escape(&item.name))?;
+        }
+        writeln!(archive, ">")?;
+        writeln!(archive, "   <mesh>")?;
+        writeln!(archive, "    <vertices>")?;
+        for v in &item.mesh.vertices {
+            writeln!(
+                archive,
+                r#"     <vertex x="{}" y="{}" z="{}"/>"#,
+                v[0], v[1], v[2]
+            )?;
+        }
+        writeln!(archive, "    </vertices>")?;
+        writeln!(archive, "    <triangles>")?;
+        for face in &item.mesh.faces {
+            let [a, b, c] = face.vertices;
+            writeln!(
+                archive,
+                r#"     <triangle v1="{}" v2="{}" v3="{}"/>"#,
+                a, b, c
+            )?;
+        }
+        writeln!(archive, "    </triangles>")?;
+        writeln!(archive, "   </mesh>")?;
+        writeln!(archive, "  </object>")?;
+    }
+    writeln!(archive, " </resources>")?;
+    writeln!(archive, " <build>")?;
+    for (i, item) in model.items.iter().enumerate() {
+        write!(archive, r#"  <item objectid="{}""#, i + 1)?;
+        if item.transform != Transform::IDENTITY {
+            write!(
+                archive,
+                r#" transform="{}""#,
+                format_transform(&item.transform)
+            )?;
+        }
+        writeln!(archive, "/>")?;
+    }
+    writeln!(archive, " </build>")?;
+    writeln!(archive, "</model>")?;
+    archive.finish()?;
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use crate::types::IndexedMesh;
+use std::collections::HashMap;
+
+/// Adjacency information of an [IndexedMesh](struct.IndexedMesh.html).
+///
+/// Every face `f` with vertices `[a, b, c]` owns the three half-edges `3 * f` (a -> b),
+/// `3 * f + 1` (b -> c) and `3 * f + 2` (c -> a). Two half-edges running in opposite directions
+/// along the same edge are twins. If an edge is used by more than two faces, half-edges are
+/// paired in face order and surplus ones stay unpaired, just like half-edges along a hole or
+/// between inconsistently wound faces.
+///
+/// The topology is a snapshot, it has to be rebuilt after the faces of the mesh are modified.
+///
+/// ```
+/// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex};
+/// let face = |vertices| IndexedTriangle { normal: Normal::new([0.0, 0.0, 1.0]), vertices };
+/// let mesh = IndexedMesh {
+///     vertices: vec![
+///         Vertex::new([0.0, 0.0, 0.0]),
+///         Vertex::new([1.0, 0.0, 0.0]),
+///         Vertex::new([1.0, 1.0, 0.0]),
+///         Vertex::new([0.0, 1.0, 0.0]),
+///     ],
+///     faces: vec![face([0, 1, 2]), face([0, 2, 3])],
+/// };
+/// let topology = mesh.topology();
+/// assert_eq!(topology.face_neighbors(0), [None, None, Some(1)]);
+/// assert_eq!(topology.vertex_faces(2), &[0, 1]);
+/// assert_eq!(topology.edge_valence(0, 2), 2);
+/// assert_eq!(topology.one_ring(0), vec![1, 2, 3]);
+/// assert!(topology.is_boundary_vertex(0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Topology {
+    faces: Vec<[usize; 3]>,
+    twins: Vec<Option<usize>>,
+    edges: HashMap<(usize, usize), Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    /// Builds the adjacency information of a mesh.
+    pub fn new(mesh: &IndexedMesh) -> Topology {
+        let faces = mesh.faces.iter().map(|f| f.vertices).collect::<Vec<_>>();
+        let mut vertex_faces = vec![Vec::new(); mesh.vertices.len()];
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (fi, face) in faces.iter().enumerate() {
+            for (i, &v) in face.iter().enumerate() {
+                // Collapsed faces must only be listed once per vertex.
+                if !face[..i].contains(&v) {
+                    vertex_faces[v].push(fi);
+                }
+                let w = face[(i + 1) % 3];
+                edges
+                    .entry((v.min(w), v.max(w)))
+                    .or_default()
+                    .push(3 * fi + i);
+            }
+        }
+
+        let mut twins = vec![None; 3 * faces.len()];
+        for half_edges in edges.values() {
+            let mut open: Vec<usize> = Vec::new();
+            for &h in half_edges {
+                let (u, v) = half_edge_vertices(&faces, h);
+                let partner = open.iter().position(|&o| {
+                    let (ou, ov) = half_edge_vertices(&faces, o);
+                    ou == v && ov == u
+                });
+                match partner {
+                    Some(pos) => {
+                        let o = open.remove(pos);
+                        twins[h] = Some(o);
+                        twins[o] = Some(h);
+                    }
+                    None => open.push(h),
+                }
+            }
+        }
+
+        Topology {
+            faces,
+            twins,
+            edges,
+            vertex_faces,
+        }
+    }
+
+    /// Number of faces of the underlying mesh.
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Number of half-edges, always three times the number of faces.
+    pub fn num_half_edges(&self) -> usize {
+        self.twins.len()
+    }
+
+    /// Returns the start and end vertex of a half-edge.
+    pub fn half_edge_vertices(&self, half_edge: usize) -> (usize, usize) {
+        half_edge_vertices(&self.faces, half_edge)
+    }
+
+    /// Returns the face a half-edge belongs to.
+    pub fn half_edge_face(&self, half_edge: usize) -> usize {
+        half_edge / 3
+    }
+
+    /// Returns the next half-edge within the same face.
+    pub fn next(&self, half_edge: usize) -> usize {
+        half_edge - half_edge % 3 + (half_edge + 1) % 3
+    }
+
+    /// Returns the previous half-edge within the same face.
+    pub fn prev(&self, half_edge: usize) -> usize {
+        half_edge - half_edge % 3 + (half_edge + 2) % 3
+    }
+
+    /// Returns the half-edge running in the opposite direction along the same edge, if any.
+    pub fn twin(&self, half_edge: usize) -> Option<usize> {
+        self.twins[half_edge]
+    }
+
+    /// Returns the faces across the three edges of a face, `None` where the edge is unpaired.
+    pub fn face_neighbors(&self, face: usize) -> [Option<usize>; 3] {
+        [0, 1, 2].map(|i| self.twins[3 * face + i].map(|t| t / 3))
+    }
+
+    /// Returns all faces that use a vertex.
+    pub fn vertex_faces(&self, vertex: usize) -> &[usize] {
+        &self.vertex_faces[vertex]
+    }
+
+    /// Returns all half-edges along the edge between two vertices, in either direction.
+    pub fn edge_half_edges(&self, u: usize, v: usize) -> &[usize] {
+        self.edges
+            .get(&(u.min(v), u.max(v)))
+            .map_or(&[], |half_edges| half_edges.as_slice())
+    }
+
+    /// Returns the number of faces using the edge between two vertices. This is 2 for edges of a
+    /// closed manifold, 1 for edges along a hole and more than 2 for non-manifold edges.
+    pub fn edge_valence(&self, u: usize, v: usize) -> usize {
+        self.edge_half_edges(u, v).len()
+    }
+
+    /// Iterates over all undirected edges as `(u, v)` with `u < v`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edges.keys().copied()
+    }
+
+    /// Returns whether a half-edge has no twin, i.e. it lies on a hole, on a non-manifold edge
+    /// or between inconsistently wound faces.
+    pub fn is_boundary_half_edge(&self, half_edge: usize) -> bool {
+        self.twins[half_edge].is_none()
+    }
+
+    /// Iterates over all half-edges without twin in ascending order.
+    pub fn boundary_half_edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.twins.len()).filter(move |&h| self.twins[h].is_none())
+    }
+
+    /// Returns whether any half-edge starting or ending at a vertex has no twin.
+    pub fn is_boundary_vertex(&self, vertex: usize) -> bool {
+        self.vertex_faces[vertex].iter().any(|&f| {
+            (3 * f..3 * f + 3).any(|h| {
+                let (u, v) = self.half_edge_vertices(h);
+                (u == vertex || v == vertex) && self.twins[h].is_none()
+            })
+        })
+    }
+
+    /// Returns the neighboring vertices of a vertex. For manifold vertices they are ordered
+    /// following the winding of the faces around the vertex, starting at a boundary if there is
+    /// one. Neighbors that cannot be reached by walking around the vertex, e.g. at non-manifold
+    /// vertices, are appended in face order.
+    pub fn one_ring(&self, vertex: usize) -> Vec<usize> {
+        let outgoing = self.vertex_faces[vertex]
+            .iter()
+            .flat_map(|&f| (0..3).map(move |i| 3 * f + i))
+            .filter(|&h| self.half_edge_vertices(h).0 == vertex)
+            .collect::<Vec<_>>();
+        let start = match outgoing
+            .iter()
+            .find(|&&h| self.twins[h].is_none())
+            .or_else(|| outgoing.first())
+        {
+            Some(&start) => start,
+            None => return Vec::new(),
+        };
+
+        let mut ring = Vec::new();
+        let mut h = start;
+        loop {
+            let (_, to) = self.half_edge_vertices(h);
+            if !ring.contains(&to) {
+                ring.push(to);
+            }
+            match self.twins[self.prev(h)] {
+                Some(t) if t != start => h = t,
+                Some(_) => break,
+                None => {
+                    let (from, _) = self.half_edge_vertices(self.prev(h));
+                    if !ring.contains(&from) {
+                        ring.push(from);
+                    }
+                    break;
+                }
+            }
+        }
+        for &h in &outgoing {
+            for v in [
+                self.half_edge_vertices(h).1,
+                self.half_edge_vertices(self.prev(h)).0,
+            ] {
+                if !ring.contains(&v) {
+                    ring.push(v);
+                }
+            }
+        }
+        ring
+    }
+}
+
+fn half_edge_vertices(faces: &[[usize; 3]], half_edge: usize) -> (usize, usize) {
+    let face = &faces[half_edge / 3];
+    (face[half_edge % 3], face[(half_edge + 1) % 3])
+}
+
+impl IndexedMesh {
+    /// Builds the [Topology](struct.Topology.html) of this mesh.
+    pub fn topology(&self) -> Topology {
+        Topology::new(self)
+    }
+}
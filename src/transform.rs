@@ -0,0 +1,267 @@
+use crate::types::{IndexedMesh, Normal, Triangle, Vertex};
+use crate::TriangleIterator;
+use std::io::Result;
+
+/// Affine transformation of 3D space, stored as a 4x4 matrix in row-major order that is applied
+/// to column vectors, i.e. the translation is in the last column.
+///
+/// Transforms are combined with [then](#method.then). Points are transformed by the matrix,
+/// normals by the inverse transpose of its linear part. Transforms that mirror space (negative
+/// determinant) also reverse the winding of the triangles, so they keep facing outward.
+///
+/// ```
+/// use stl_io::{Transform, Vertex};
+/// let t = Transform::uniform_scale(2.0).then(&Transform::translation([1.0, 0.0, 0.0]));
+/// assert_eq!(t.transform_point(Vertex::new([1.0, 1.0, 1.0])), Vertex::new([3.0, 2.0, 2.0]));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    matrix: [[f32; 4]; 4],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// The transform that leaves everything in place.
+    pub const IDENTITY: Transform = Transform {
+        matrix: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    /// Creates a transform from a row-major 4x4 matrix. The last row should be `[0, 0, 0, 1]`,
+    /// projective matrices are applied without the perspective division.
+    pub const fn from_matrix(matrix: [[f32; 4]; 4]) -> Transform {
+        Transform { matrix }
+    }
+
+    /// Returns the row-major 4x4 matrix of this transform.
+    pub const fn matrix(&self) -> [[f32; 4]; 4] {
+        self.matrix
+    }
+
+    // Creates a transform from its linear part and translation.
+    fn from_linear(linear: [[f32; 3]; 3], translation: [f32; 3]) -> Transform {
+        let mut matrix = Self::IDENTITY.matrix;
+        for r in 0..3 {
+            matrix[r][..3].copy_from_slice(&linear[r]);
+            matrix[r][3] = translation[r];
+        }
+        Transform { matrix }
+    }
+
+    /// Moves everything by `offset`.
+    pub fn translation(offset: [f32; 3]) -> Transform {
+        Self::from_linear([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], offset)
+    }
+
+    /// Scales independently along the x, y and z axis. Negative factors mirror.
+    pub fn scale(factors: [f32; 3]) -> Transform {
+        let [x, y, z] = factors;
+        Self::from_linear([[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]], [0.0; 3])
+    }
+
+    /// Scales by the same factor along all axes.
+    pub fn uniform_scale(factor: f32) -> Transform {
+        Self::scale([factor; 3])
+    }
+
+    /// Mirrors at the plane through the origin with the given normal.
+    pub fn mirror(normal: [f32; 3]) -> Transform {
+        let l2 = normal.iter().map(|c| c * c).sum::<f32>();
+        let mut linear = [[0.0; 3]; 3];
+        for (r, row) in linear.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                let identity = if r == c { 1.0 } else { 0.0 };
+                *value = identity - 2.0 * normal[r] * normal[c] / l2;
+            }
+        }
+        Self::from_linear(linear, [0.0; 3])
+    }
+
+    /// Rotates counter-clockwise by `angle` radians around `axis`, which does not need to be
+    /// normalized.
+    pub fn rotation_axis_angle(axis: [f32; 3], angle: f32) -> Transform {
+        let l = axis.iter().map(|c| c * c).sum::<f32>().sqrt();
+        let (s, c) = (angle / 2.0).sin_cos();
+        Self::rotation_quaternion([axis[0] / l * s, axis[1] / l * s, axis[2] / l * s, c])
+    }
+
+    /// Rotates by the unit quaternion `[x, y, z, w]`, with `w` being the scalar part.
+    /// The quaternion is normalized first.
+    pub fn rotation_quaternion(quaternion: [f32; 4]) -> Transform {
+        let l = quaternion.iter().map(|c| c * c).sum::<f32>().sqrt();
+        let [x, y, z, w] = quaternion.map(|c| c / l);
+        Self::from_linear(
+            [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - z * w),
+                    2.0 * (x * z + y * w),
+                ],
+                [
+                    2.0 * (x * y + z * w),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - x * w),
+                ],
+                [
+                    2.0 * (x * z - y * w),
+                    2.0 * (y * z + x * w),
+                    1.0 - 2.0 * (x * x + y * y),
+                ],
+            ],
+            [0.0; 3],
+        )
+    }
+
+    /// Rotates by `angles[0]` radians around the x axis, then by `angles[1]` around the y axis and
+    /// finally by `angles[2]` around the z axis. All rotations are around the fixed world axes.
+    pub fn rotation_euler(angles: [f32; 3]) -> Transform {
+        Self::rotation_axis_angle([1.0, 0.0, 0.0], angles[0])
+            .then(&Self::rotation_axis_angle([0.0, 1.0, 0.0], angles[1]))
+            .then(&Self::rotation_axis_angle([0.0, 0.0, 1.0], angles[2]))
+    }
+
+    /// Returns the transform that first applies `self` and then `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        let mut matrix = [[0.0; 4]; 4];
+        for (r, row) in matrix.iter_mut().enumerate() {
+            for (c, value) in row.iter_mut().enumerate() {
+                *value = (0..4)
+                    .map(|k| f64::from(next.matrix[r][k]) * f64::from(self.matrix[k][c]))
+                    .sum::<f64>() as f32;
+            }
+        }
+        Transform { matrix }
+    }
+
+    // Determinant of the linear part.
+    fn determinant(&self) -> f32 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Returns whether this transform mirrors space, i.e. reverses the winding of triangles.
+    pub fn is_mirroring(&self) -> bool {
+        self.determinant() < 0.0
+    }
+
+    /// Applies the transform to a point.
+    pub fn transform_point(&self, point: Vertex) -> Vertex {
+        let m = &self.matrix;
+        Vertex::new(
+            [0, 1, 2]
+                .map(|r| m[r][0] * point[0] + m[r][1] * point[1] + m[r][2] * point[2] + m[r][3]),
+        )
+    }
+
+    /// Applies the inverse transpose of the linear part to a normal and normalizes the result.
+    /// A zero normal stays zero.
+    pub fn transform_normal(&self, normal: Normal) -> Normal {
+        let m = &self.matrix;
+        // The cofactor matrix is the inverse transpose scaled by the determinant.
+        let cofactor = |r: usize, c: usize| {
+            let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+            let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+            m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+        };
+        let sign = if self.is_mirroring() { -1.0 } else { 1.0 };
+        let n = [0, 1, 2].map(|r| {
+            sign * (cofactor(r, 0) * normal[0]
+                + cofactor(r, 1) * normal[1]
+                + cofactor(r, 2) * normal[2])
+        });
+        let l = super::utils::length(Normal::new(n));
+        if l > 0.0 {
+            Normal::new(n.map(|c| c / l))
+        } else {
+            Normal::default()
+        }
+    }
+}
+
+impl Triangle {
+    /// Applies a [Transform](struct.Transform.html) to the vertices and the normal, reversing
+    /// the winding if the transform mirrors.
+    pub fn transform(&mut self, transform: &Transform) {
+        self.normal = transform.transform_normal(self.normal);
+        for v in &mut self.vertices {
+            *v = transform.transform_point(*v);
+        }
+        if transform.is_mirroring() {
+            self.vertices.swap(1, 2);
+        }
+    }
+}
+
+impl IndexedMesh {
+    /// Applies a [Transform](struct.Transform.html) to all vertices and normals, reversing the
+    /// winding of all faces if the transform mirrors.
+    ///
+    /// ```
+    /// let mut reader = ::std::io::Cursor::new(b"solid foobar
+    /// facet normal 0 0 1
+    ///     outer loop
+    ///         vertex 0 0 0
+    ///         vertex 1 0 0
+    ///         vertex 0 1 0
+    ///     endloop
+    /// endfacet
+    /// endsolid foobar".to_vec());
+    /// let mut mesh = stl_io::read_stl(&mut reader).unwrap();
+    /// mesh.transform(&stl_io::Transform::mirror([0.0, 0.0, 1.0]));
+    /// assert_eq!(mesh.faces[0].vertices, [0, 2, 1]);
+    /// assert_eq!(mesh.faces[0].normal, stl_io::Normal::new([0.0, 0.0, -1.0]));
+    /// ```
+    pub fn transform(&mut self, transform: &Transform) {
+        for v in &mut self.vertices {
+            *v = transform.transform_point(*v);
+        }
+        let mirroring = transform.is_mirroring();
+        for face in &mut self.faces {
+            face.normal = transform.transform_normal(face.normal);
+            if mirroring {
+                face.vertices.swap(1, 2);
+            }
+        }
+    }
+}
+
+/// Lazily transforms the triangles of another [TriangleIterator](trait.TriangleIterator.html),
+/// see [TriangleIterator::transformed](trait.TriangleIterator.html#method.transformed).
+pub struct Transformed<I> {
+    inner: I,
+    transform: Transform,
+}
+
+impl<I> Transformed<I> {
+    pub(crate) fn new(inner: I, transform: Transform) -> Self {
+        Transformed { inner, transform }
+    }
+}
+
+impl<I: Iterator<Item = Result<Triangle>>> Iterator for Transformed<I> {
+    type Item = Result<Triangle>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|t| {
+            t.map(|mut t| {
+                t.transform(&self.transform);
+                t
+            })
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: TriangleIterator> TriangleIterator for Transformed<I> {}
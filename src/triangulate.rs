@@ -0,0 +1,242 @@
+// Ear clipping triangulation of 2D polygons with holes.
+
+use crate::slice::Plane;
+use crate::types::{Normal, Vertex};
+
+type Point = [f64; 2];
+
+// Twice the signed area of the triangle abc, positive if it is counter-clockwise.
+fn orient(a: Point, b: Point, c: Point) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn distance(a: Point, b: Point) -> f64 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+// Corners whose sine of the angle is below this are considered straight.
+const COLLINEAR: f64 = 1e-5;
+
+fn area(points: &[Point], ring: &[usize]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let a = points[ring[i]];
+            let b = points[ring[(i + 1) % ring.len()]];
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+}
+
+fn inside(points: &[Point], ring: &[usize], p: Point) -> bool {
+    let polygon = ring
+        .iter()
+        .map(|&i| [points[i][0] as f32, points[i][1] as f32])
+        .collect::<Vec<_>>();
+    super::utils::point_in_polygon([p[0] as f32, p[1] as f32], &polygon)
+}
+
+// Whether the segments pq and rs intersect anywhere but in shared endpoints.
+fn segments_intersect(p: Point, q: Point, r: Point, s: Point) -> bool {
+    if p == r || p == s || q == r || q == s {
+        // Sharing an endpoint, they only conflict if they overlap.
+        return orient(p, q, r) == 0.0
+            && orient(p, q, s) == 0.0
+            && [r, s].iter().any(|&x| {
+                x != p
+                    && x != q
+                    && (x[0] - p[0]) * (x[0] - q[0]) <= 0.0
+                    && (x[1] - p[1]) * (x[1] - q[1]) <= 0.0
+            });
+    }
+    let d1 = orient(r, s, p);
+    let d2 = orient(r, s, q);
+    let d3 = orient(p, q, r);
+    let d4 = orient(p, q, s);
+    let on = |a: Point, b: Point, x: Point| {
+        (x[0] - a[0]) * (x[0] - b[0]) <= 0.0 && (x[1] - a[1]) * (x[1] - b[1]) <= 0.0
+    };
+    ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+        || (d1 == 0.0 && on(r, s, p))
+        || (d2 == 0.0 && on(r, s, q))
+        || (d3 == 0.0 && on(p, q, r))
+        || (d4 == 0.0 && on(p, q, s))
+}
+
+/// Triangulates a polygon with holes. The outer boundary and the holes may be given in either
+/// orientation, the resulting triangles are counter-clockwise. Indices refer to the points of
+/// `outer` followed by the points of all holes in order.
+pub fn triangulate(outer: &[[f32; 2]], holes: &[Vec<[f32; 2]>]) -> Vec<[usize; 3]> {
+    let points = outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .map(|p| [f64::from(p[0]), f64::from(p[1])])
+        .collect::<Vec<_>>();
+    let mut ring = (0..outer.len()).collect::<Vec<_>>();
+    if area(&points, &ring) < 0.0 {
+        ring.reverse();
+    }
+    let outer_ring = ring.clone();
+    let mut hole_rings = Vec::new();
+    let mut offset = outer.len();
+    for hole in holes {
+        let mut hole_ring = (offset..offset + hole.len()).collect::<Vec<_>>();
+        if area(&points, &hole_ring) > 0.0 {
+            hole_ring.reverse();
+        }
+        offset += hole.len();
+        if hole_ring.len() >= 3 {
+            hole_rings.push(hole_ring);
+        }
+    }
+
+    // Connect holes to the outer boundary, rightmost holes first so that bridges of later holes
+    // cannot be blocked by the earlier ones.
+    hole_rings.sort_by(|a, b| {
+        let max_x = |r: &Vec<usize>| r.iter().map(|&i| points[i][0]).fold(f64::MIN, f64::max);
+        max_x(b).total_cmp(&max_x(a))
+    });
+    for (hi, hole) in hole_rings.iter().enumerate() {
+        // Holes that overlap the boundary or each other have no clean bridge, they are still
+        // connected, so that their area is not filled.
+        let (m, p) = find_bridge(&points, &ring, &outer_ring, &hole_rings[hi..], hole)
+            .unwrap_or_else(|| closest_pair(&points, &ring, hole));
+        // ring[..=p], hole[m..], hole[..=m], ring[p..]
+        let mut merged = Vec::with_capacity(ring.len() + hole.len() + 2);
+        merged.extend_from_slice(&ring[..=p]);
+        merged.extend(hole[m..].iter().chain(hole[..=m].iter()));
+        merged.extend_from_slice(&ring[p..]);
+        ring = merged;
+    }
+    ear_clip(&points, ring)
+}
+
+// Finds a hole vertex (index into hole) and a ring vertex (index into ring) that can be connected
+// without crossing any edge of the ring or the remaining holes.
+fn find_bridge(
+    points: &[Point],
+    ring: &[usize],
+    outer: &[usize],
+    remaining_holes: &[Vec<usize>],
+    hole: &[usize],
+) -> Option<(usize, usize)> {
+    let mut hole_order = (0..hole.len()).collect::<Vec<_>>();
+    hole_order.sort_by(|&a, &b| points[hole[b]][0].total_cmp(&points[hole[a]][0]));
+    for m in hole_order {
+        let mp = points[hole[m]];
+        let mut candidates = (0..ring.len()).collect::<Vec<_>>();
+        let distance = |i: usize| {
+            let p = points[ring[i]];
+            (p[0] - mp[0]).powi(2) + (p[1] - mp[1]).powi(2)
+        };
+        candidates.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)));
+        for p in candidates {
+            let pp = points[ring[p]];
+            let edges = ring.iter().zip(ring.iter().cycle().skip(1)).chain(
+                remaining_holes
+                    .iter()
+                    .flat_map(|h| h.iter().zip(h.iter().cycle().skip(1))),
+            );
+            if edges
+                .clone()
+                .any(|(&a, &b)| segments_intersect(mp, pp, points[a], points[b]))
+            {
+                continue;
+            }
+            let mid = [(mp[0] + pp[0]) / 2.0, (mp[1] + pp[1]) / 2.0];
+            if inside(points, outer, mid) && !remaining_holes.iter().any(|h| inside(points, h, mid))
+            {
+                return Some((m, p));
+            }
+        }
+    }
+    None
+}
+
+// The closest hole vertex (index into hole) and ring vertex (index into ring).
+fn closest_pair(points: &[Point], ring: &[usize], hole: &[usize]) -> (usize, usize) {
+    (0..hole.len())
+        .flat_map(|m| (0..ring.len()).map(move |p| (m, p)))
+        .min_by(|&(m, p), &(n, q)| {
+            let d = |m: usize, p: usize| distance(points[hole[m]], points[ring[p]]);
+            d(m, p).total_cmp(&d(n, q))
+        })
+        .unwrap()
+}
+
+fn ear_clip(points: &[Point], mut ring: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    while ring.len() > 3 {
+        let n = ring.len();
+        let corner = |i: usize| (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let is_ear = |i: usize| {
+            let (a, b, c) = corner(i);
+            let (pa, pb, pc) = (points[a], points[b], points[c]);
+            // Points come from f32 coordinates, so nearly collinear corners must not become
+            // ears, they would turn into zero-area triangles.
+            let tolerance = COLLINEAR * distance(pa, pb) * distance(pb, pc);
+            if orient(pa, pb, pc) <= tolerance {
+                return false;
+            }
+            !ring.iter().any(|&x| {
+                let px = points[x];
+                px != pa
+                    && px != pb
+                    && px != pc
+                    && orient(pa, pb, px) >= -tolerance
+                    && orient(pb, pc, px) >= -tolerance
+                    && orient(pc, pa, px) >= -tolerance
+            })
+        };
+        // Fall back to the most convex corner if rounding errors left no proper ear.
+        let ear = (0..n).find(|&i| is_ear(i)).unwrap_or_else(|| {
+            (0..n)
+                .max_by(|&i, &j| {
+                    let area = |i: usize| {
+                        let (a, b, c) = corner(i);
+                        orient(points[a], points[b], points[c])
+                    };
+                    area(i).total_cmp(&area(j))
+                })
+                .unwrap()
+        });
+        let (a, b, c) = corner(ear);
+        triangles.push([a, b, c]);
+        ring.remove(ear);
+    }
+    if ring.len() == 3 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    triangles
+}
+
+/// Triangulates a planar polygon in 3D, given by vertex indices, keeping its winding. Indices of
+/// the result refer to `vertices` like those of `face`.
+pub fn triangulate_face(vertices: &[Vertex], face: &[usize]) -> Vec<[usize; 3]> {
+    if face.len() == 3 {
+        return vec![[face[0], face[1], face[2]]];
+    }
+    // Newell's method gives a normal that works for non-convex polygons, too.
+    let mut normal = [0.0f32; 3];
+    for (k, &a) in face.iter().enumerate() {
+        let (p, q) = (vertices[a], vertices[face[(k + 1) % face.len()]]);
+        normal[0] += (p[1] - q[1]) * (p[2] + q[2]);
+        normal[1] += (p[2] - q[2]) * (p[0] + q[0]);
+        normal[2] += (p[0] - q[0]) * (p[1] + q[1]);
+    }
+    if normal == [0.0; 3] {
+        return (1..face.len().saturating_sub(1))
+            .map(|k| [face[0], face[k], face[k + 1]])
+            .collect();
+    }
+    // The polygon runs counter-clockwise around its normal, so it does so in the plane's basis.
+    let plane = Plane::new(Normal::new(normal), vertices[face[0]]);
+    let points = face
+        .iter()
+        .map(|&v| plane.project(vertices[v]))
+        .collect::<Vec<_>>();
+    triangulate(&points, &[])
+        .into_iter()
+        .map(|t| t.map(|k| face[k]))
+        .collect()
+}
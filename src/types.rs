@@ -1,5 +1,4 @@
 use float_cmp::ApproxEq;
-use std::collections::HashMap;
 use std::io::Result;
 
 /// Float Vector with approx_eq.
@@ -27,7 +26,9 @@ impl<F> std::ops::Index<usize> for Vector<F> {
     }
 }
 
-impl<'a, M: Copy + Default + float_cmp::FloatMargin, F: Copy + ApproxEq<Margin = M>> ApproxEq for &'a Vector<F> {
+impl<M: Copy + Default + float_cmp::FloatMargin, F: Copy + ApproxEq<Margin = M>> ApproxEq
+    for &Vector<F>
+{
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -80,42 +81,29 @@ impl IndexedMesh {
     /// Checks that the Mesh has no holes and no zero-area faces.
     /// Also makes sure that all triangles are faced in the same direction.
     pub fn validate(&self) -> Result<()> {
-        let mut unconnected_edges: HashMap<(usize, usize), (usize, usize, usize)> = HashMap::new();
-
         for (fi, face) in self.faces.iter().enumerate() {
-            {
-                let a = self.vertices[face.vertices[0]];
-                let b = self.vertices[face.vertices[1]];
-                let c = self.vertices[face.vertices[2]];
-
-                let area = super::utils::tri_area(a, b, c);
-
-                if area < f32::EPSILON {
-                    return Err(::std::io::Error::new(
-                        ::std::io::ErrorKind::InvalidData,
-                        format!("face #{} has a zero-area face", fi),
-                    ));
-                }
-            }
+            let a = self.vertices[face.vertices[0]];
+            let b = self.vertices[face.vertices[1]];
+            let c = self.vertices[face.vertices[2]];
 
-            for i in 0..3 {
-                let u = face.vertices[i];
-                let v = face.vertices[(i + 1) % 3];
+            let area = super::utils::tri_area(a, b, c);
 
-                if unconnected_edges.contains_key(&(v, u)) {
-                    unconnected_edges.remove(&(v, u));
-                } else {
-                    unconnected_edges.insert((u, v), (fi, i, (i + 1) % 3));
-                }
+            if area < f32::EPSILON {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    format!("face #{} has a zero-area face", fi),
+                ));
             }
         }
 
-        if let Option::Some((fi, i1, i2)) = unconnected_edges.values().into_iter().next() {
+        if let Some(h) = self.topology().boundary_half_edges().next() {
             Err(::std::io::Error::new(
                 ::std::io::ErrorKind::InvalidData,
                 format!(
                     "did not find facing edge for face #{}, edge #v{} -> #v{}",
-                    fi, i1, i2
+                    h / 3,
+                    h % 3,
+                    (h + 1) % 3
                 ),
             ))
         } else {
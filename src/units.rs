@@ -0,0 +1,178 @@
+use crate::types::{IndexedMesh, Vertex};
+
+/// Length unit of the coordinates in a mesh. STL itself is unitless, so units have to be
+/// guessed or agreed upon, see [from_text](#method.from_text) and
+/// [guess_from_bounds](#method.guess_from_bounds).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Units {
+    /// 1e-6 meters.
+    Micrometers,
+    /// 1e-3 meters, the de facto standard for 3D printing.
+    Millimeters,
+    /// 1e-2 meters.
+    Centimeters,
+    /// Meters.
+    Meters,
+    /// 25.4 millimeters.
+    Inches,
+    /// 12 inches.
+    Feet,
+}
+
+// Names accepted by from_text, the first one of every unit is used when writing.
+const NAMES: &[(Units, &[&str])] = &[
+    (
+        Units::Micrometers,
+        &[
+            "um",
+            "micrometer",
+            "micrometers",
+            "micrometre",
+            "micrometres",
+            "micron",
+            "microns",
+        ],
+    ),
+    (
+        Units::Millimeters,
+        &[
+            "mm",
+            "millimeter",
+            "millimeters",
+            "millimetre",
+            "millimetres",
+        ],
+    ),
+    (
+        Units::Centimeters,
+        &[
+            "cm",
+            "centimeter",
+            "centimeters",
+            "centimetre",
+            "centimetres",
+        ],
+    ),
+    (Units::Meters, &["m", "meter", "meters", "metre", "metres"]),
+    (Units::Inches, &["in", "inch", "inches"]),
+    (Units::Feet, &["ft", "foot", "feet"]),
+];
+
+impl Units {
+    /// Length of one unit in millimeters.
+    pub fn in_millimeters(self) -> f64 {
+        match self {
+            Units::Micrometers => 0.001,
+            Units::Millimeters => 1.0,
+            Units::Centimeters => 10.0,
+            Units::Meters => 1000.0,
+            Units::Inches => 25.4,
+            Units::Feet => 304.8,
+        }
+    }
+
+    /// Factor to multiply coordinates with to convert them from `self` to `to`.
+    pub fn conversion_factor(self, to: Units) -> f32 {
+        (self.in_millimeters() / to.in_millimeters()) as f32
+    }
+
+    /// Short name of the unit, e.g. `"mm"` or `"in"`.
+    pub fn abbreviation(self) -> &'static str {
+        NAMES.iter().find(|(u, _)| *u == self).unwrap().1[0]
+    }
+
+    /// Text to record the unit in a STL header, e.g. `"UNITS=mm"`. It is recognized by
+    /// [from_text](#method.from_text) and meant to be used with
+    /// [write_stl_with_header](fn.write_stl_with_header.html).
+    pub fn header_text(self) -> String {
+        format!("UNITS={}", self.abbreviation())
+    }
+
+    /// Looks for a unit declaration like `UNITS=in`, `units: mm` or `unit = inch` in a STL header
+    /// or solid name, as returned by [read_stl_header](fn.read_stl_header.html). Matching is case
+    /// insensitive.
+    ///
+    /// ```
+    /// use stl_io::Units;
+    /// assert_eq!(Units::from_text("Exported by CAD UNITS=in"), Some(Units::Inches));
+    /// assert_eq!(Units::from_text("bracket, unit: Millimeters"), Some(Units::Millimeters));
+    /// assert_eq!(Units::from_text("solid bracket"), None);
+    /// ```
+    pub fn from_text(text: &str) -> Option<Units> {
+        let lower = text.to_ascii_lowercase();
+        let mut rest = lower.as_str();
+        while let Some(pos) = rest.find("unit") {
+            rest = &rest[pos + "unit".len()..];
+            let value = rest
+                .trim_start_matches('s')
+                .trim_start_matches(|c: char| c.is_whitespace() || c == '=' || c == ':');
+            let token = value
+                .split(|c: char| !c.is_ascii_alphabetic())
+                .next()
+                .unwrap_or("");
+            if let Some((units, _)) = NAMES.iter().find(|(_, names)| names.contains(&token)) {
+                return Some(*units);
+            }
+        }
+        None
+    }
+
+    /// Guesses the units of a mesh from its size, assuming it is a part between 5 mm and 500 mm
+    /// in its largest dimension, as typical for 3D printing.
+    ///
+    /// Candidates are millimeters, inches and meters, in this order. If several of them give a
+    /// plausible size, the first one in which the extents of the bounding box are round numbers
+    /// (whole millimeters, sixteenths of an inch or whole meters) is preferred, otherwise the
+    /// first plausible one.
+    /// Returns `None` for empty meshes or if no candidate gives a plausible size.
+    pub fn guess_from_bounds(mesh: &IndexedMesh) -> Option<Units> {
+        let (min, max) = mesh.bounding_box()?;
+        let extents = [0, 1, 2].map(|i| f64::from(max[i] - min[i]));
+        let largest = extents.iter().copied().fold(0.0, f64::max);
+        let plausible = [Units::Millimeters, Units::Inches, Units::Meters]
+            .into_iter()
+            .filter(|u| (5.0..=500.0).contains(&(largest * u.in_millimeters())))
+            .collect::<Vec<_>>();
+        let is_round = |u: Units| {
+            let step = if u == Units::Inches { 1.0 / 16.0 } else { 1.0 };
+            extents.iter().filter(|&&e| e > 0.0).all(|&e| {
+                let steps = e / step;
+                (steps - steps.round()).abs() < 1e-3
+            })
+        };
+        plausible
+            .iter()
+            .copied()
+            .find(|&u| is_round(u))
+            .or_else(|| plausible.first().copied())
+    }
+}
+
+impl IndexedMesh {
+    /// Returns the minimum and maximum corner of the axis aligned bounding box, or `None` if the
+    /// mesh has no vertices.
+    pub fn bounding_box(&self) -> Option<(Vertex, Vertex)> {
+        let first = *self.vertices.first()?;
+        Some(self.vertices.iter().fold((first, first), |(min, max), v| {
+            (
+                Vertex::new([0, 1, 2].map(|i| min[i].min(v[i]))),
+                Vertex::new([0, 1, 2].map(|i| max[i].max(v[i]))),
+            )
+        }))
+    }
+
+    /// Scales all vertices to convert them from one unit to another.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, Units, Vertex};
+    /// let mut mesh = IndexedMesh { vertices: vec![Vertex::new([1.0, 2.0, 0.5])], faces: vec![] };
+    /// mesh.convert_units(Units::Inches, Units::Millimeters);
+    /// assert_eq!(mesh.vertices[0], Vertex::new([25.4, 50.8, 12.7]));
+    /// ```
+    pub fn convert_units(&mut self, from: Units, to: Units) {
+        let factor = from.conversion_factor(to);
+        for v in &mut self.vertices {
+            *v = Vertex::new(v.0.map(|c| c * factor));
+        }
+    }
+}
@@ -1,22 +1,72 @@
-use crate::types::Vertex;
+use crate::types::{Normal, Vertex};
+
+pub fn sub(a: Vertex, b: Vertex) -> Vertex {
+    Vertex::new([a[0] - b[0], a[1] - b[1], a[2] - b[2]])
+}
+
+pub fn cross(a: Vertex, b: Vertex) -> Vertex {
+    let x = a[1] * b[2] - a[2] * b[1];
+    let y = a[2] * b[0] - a[0] * b[2];
+    let z = a[0] * b[1] - a[1] * b[0];
+    Vertex::new([x, y, z])
+}
+
+pub fn dot(a: Vertex, b: Vertex) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn length(v: Vertex) -> f32 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+/// Unit normal of a triangle with counter-clockwise winding, zero for degenerate triangles.
+pub fn normal(a: Vertex, b: Vertex, c: Vertex) -> Normal {
+    let n = cross(sub(b, a), sub(c, a));
+    let l = length(n);
+    if l > 0.0 {
+        Normal::new(n.0.map(|c| c / l))
+    } else {
+        Normal::default()
+    }
+}
 
 /// Calculate the area of a triangle
 pub fn tri_area(a: Vertex, b: Vertex, c: Vertex) -> f32 {
-    fn cross(a: Vertex, b: Vertex) -> Vertex {
-        let x = a[1] * b[2] - a[2] * b[1];
-        let y = a[2] * b[0] - a[0] * b[2];
-        let z = a[0] * b[1] - a[1] * b[0];
-        Vertex::new([x, y, z])
-    }
-    fn sub(a: Vertex, b: Vertex) -> Vertex {
-        let x = a[0] - b[0];
-        let y = a[1] - b[1];
-        let z = a[2] - b[2];
-        Vertex::new([x, y, z])
-    }
-    fn length(v: Vertex) -> f32 {
-        (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+    length(cross(sub(c, b), sub(a, b))) * 0.5
+}
+
+/// Signed volume of the tetrahedron spanned by the origin and the triangle. Summed over all faces
+/// of a closed mesh this gives the enclosed volume, positive if the faces point outward.
+pub fn signed_volume(a: Vertex, b: Vertex, c: Vertex) -> f64 {
+    let a = a.0.map(f64::from);
+    let b = b.0.map(f64::from);
+    let c = c.0.map(f64::from);
+    (a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+        + a[2] * (b[0] * c[1] - b[1] * c[0]))
+        / 6.0
+}
+
+/// Signed area of a closed 2D polygon, positive if it runs counter-clockwise.
+pub fn polygon_area(polygon: &[[f32; 2]]) -> f64 {
+    let mut area = 0.0;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        area += f64::from(a[0]) * f64::from(b[1]) - f64::from(b[0]) * f64::from(a[1]);
     }
+    area / 2.0
+}
 
-    length(cross(sub(c, b), sub(a, b))) * 0.5
+/// Even-odd test whether a point lies inside a closed 2D polygon.
+pub fn point_in_polygon(point: [f32; 2], polygon: &[[f32; 2]]) -> bool {
+    let mut inside = false;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if (a[1] > point[1]) != (b[1] > point[1]) {
+            let x = a[0] + (point[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]);
+            if point[0] < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
 }
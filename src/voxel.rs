@@ -0,0 +1,318 @@
+use crate::bvh::{triangle_box_overlap, Bvh, Ray};
+use crate::types::{IndexedMesh, Vector, Vertex};
+use std::collections::BTreeSet;
+use std::io::Result;
+
+/// Which voxels [voxelize](struct.IndexedMesh.html#method.voxelize) sets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoxelFill {
+    /// Only voxels touched by a face.
+    Surface,
+    /// Voxels touched by a face and voxels whose center lies inside the mesh.
+    Solid,
+}
+
+/// Dense grid of cubic voxels, see [voxelize](struct.IndexedMesh.html#method.voxelize).
+///
+/// Voxel `[x, y, z]` covers the cube from `origin + spacing * [x, y, z]` to
+/// `origin + spacing * [x + 1, y + 1, z + 1]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelGrid {
+    /// Minimum corner of the first voxel.
+    pub origin: Vertex,
+    /// Edge length of the voxels.
+    pub spacing: f32,
+    /// Number of voxels along x, y and z.
+    pub dimensions: [usize; 3],
+    /// Whether each voxel is set, with x varying fastest, then y, then z.
+    pub voxels: Vec<bool>,
+}
+
+/// Sparse grid of cubic voxels, like [VoxelGrid](struct.VoxelGrid.html) but only storing the
+/// coordinates of the voxels that are set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseVoxelGrid {
+    /// Minimum corner of the first voxel.
+    pub origin: Vertex,
+    /// Edge length of the voxels.
+    pub spacing: f32,
+    /// Number of voxels along x, y and z.
+    pub dimensions: [usize; 3],
+    /// Coordinates of the voxels that are set.
+    pub voxels: BTreeSet<[usize; 3]>,
+}
+
+impl VoxelGrid {
+    /// Position of a voxel in [voxels](#structfield.voxels).
+    pub fn index(&self, voxel: [usize; 3]) -> usize {
+        voxel[0] + self.dimensions[0] * (voxel[1] + self.dimensions[1] * voxel[2])
+    }
+
+    /// Whether a voxel is set, `false` outside of the grid.
+    pub fn get(&self, voxel: [usize; 3]) -> bool {
+        (0..3).all(|c| voxel[c] < self.dimensions[c]) && self.voxels[self.index(voxel)]
+    }
+
+    /// Center of a voxel.
+    pub fn center(&self, voxel: [usize; 3]) -> Vertex {
+        voxel_center(self.origin, self.spacing, voxel)
+    }
+
+    /// Number of voxels that are set.
+    pub fn count(&self) -> usize {
+        self.voxels.iter().filter(|&&v| v).count()
+    }
+
+    /// Converts the grid into a [SparseVoxelGrid](struct.SparseVoxelGrid.html).
+    pub fn to_sparse(&self) -> SparseVoxelGrid {
+        let [dx, dy, dz] = self.dimensions;
+        SparseVoxelGrid {
+            origin: self.origin,
+            spacing: self.spacing,
+            dimensions: self.dimensions,
+            voxels: (0..dz)
+                .flat_map(|z| (0..dy).flat_map(move |y| (0..dx).map(move |x| [x, y, z])))
+                .filter(|&v| self.voxels[self.index(v)])
+                .collect(),
+        }
+    }
+}
+
+impl SparseVoxelGrid {
+    /// Whether a voxel is set.
+    pub fn get(&self, voxel: [usize; 3]) -> bool {
+        self.voxels.contains(&voxel)
+    }
+
+    /// Center of a voxel.
+    pub fn center(&self, voxel: [usize; 3]) -> Vertex {
+        voxel_center(self.origin, self.spacing, voxel)
+    }
+
+    /// Converts the grid into a dense [VoxelGrid](struct.VoxelGrid.html).
+    pub fn to_dense(&self) -> VoxelGrid {
+        let mut grid = VoxelGrid {
+            origin: self.origin,
+            spacing: self.spacing,
+            dimensions: self.dimensions,
+            voxels: vec![false; self.dimensions.iter().product()],
+        };
+        for &voxel in &self.voxels {
+            let index = grid.index(voxel);
+            grid.voxels[index] = true;
+        }
+        grid
+    }
+}
+
+fn voxel_center(origin: Vertex, spacing: f32, voxel: [usize; 3]) -> Vertex {
+    Vertex::new([0, 1, 2].map(|c| origin[c] + (voxel[c] as f32 + 0.5) * spacing))
+}
+
+impl IndexedMesh {
+    /// Converts the mesh into a dense grid of voxels with edge length `spacing`.
+    ///
+    /// The grid starts at the minimum corner of the faces' bounding box and has just enough
+    /// voxels to cover it. A mesh without faces gives an empty grid. Fails with `InvalidInput` if
+    /// `spacing` is not positive and finite, or if the grid would have more voxels than fit into
+    /// memory addresses.
+    /// For [VoxelFill::Solid](enum.VoxelFill.html#variant.Solid), inside and outside are told
+    /// apart by counting crossings of the surface along every column of voxels, so faces should
+    /// point outward, and nested shells add up. Columns passing through holes in the mesh, as in
+    /// scanned parts, fall back to the [winding number](struct.Bvh.html#method.winding_number),
+    /// so small holes do not leak.
+    ///
+    /// ```
+    /// use stl_io::{IndexedMesh, IndexedTriangle, Normal, Vertex, VoxelFill};
+    /// let face = |vertices| IndexedTriangle { normal: Normal::default(), vertices };
+    /// let tetrahedron = IndexedMesh {
+    ///     vertices: vec![
+    ///         Vertex::new([0.0, 0.0, 0.0]),
+    ///         Vertex::new([3.0, 0.0, 0.0]),
+    ///         Vertex::new([0.0, 3.0, 0.0]),
+    ///         Vertex::new([0.0, 0.0, 3.0]),
+    ///     ],
+    ///     faces: vec![face([0, 2, 1]), face([0, 1, 3]), face([1, 2, 3]), face([0, 3, 2])],
+    /// };
+    /// let grid = tetrahedron.voxelize(0.4, VoxelFill::Solid).unwrap();
+    /// assert_eq!(grid.dimensions, [8, 8, 8]);
+    /// assert!(grid.get([1, 1, 1]));
+    /// assert!(!grid.get([7, 7, 7]));
+    /// assert!(grid.count() > tetrahedron.voxelize(0.4, VoxelFill::Surface).unwrap().count());
+    /// assert!(tetrahedron.voxelize(0.0, VoxelFill::Solid).is_err());
+    /// ```
+    pub fn voxelize(&self, spacing: f32, fill: VoxelFill) -> Result<VoxelGrid> {
+        let (origin, dimensions) = voxel_frame(self, spacing)?;
+        let mut grid = VoxelGrid {
+            origin,
+            spacing,
+            dimensions,
+            voxels: vec![false; dimensions.iter().product()],
+        };
+        visit_voxels(self, origin, spacing, dimensions, fill, |voxel| {
+            let index = grid.index(voxel);
+            grid.voxels[index] = true;
+        });
+        Ok(grid)
+    }
+
+    /// Converts the mesh into a sparse grid of voxels, see [voxelize](#method.voxelize).
+    /// The memory used only depends on the number of voxels set, not on the size of the grid.
+    pub fn voxelize_sparse(&self, spacing: f32, fill: VoxelFill) -> Result<SparseVoxelGrid> {
+        let (origin, dimensions) = voxel_frame(self, spacing)?;
+        let mut voxels = BTreeSet::new();
+        visit_voxels(self, origin, spacing, dimensions, fill, |voxel| {
+            voxels.insert(voxel);
+        });
+        Ok(SparseVoxelGrid {
+            origin,
+            spacing,
+            dimensions,
+            voxels,
+        })
+    }
+}
+
+// Origin and size of the grid covering the faces of a mesh.
+fn voxel_frame(mesh: &IndexedMesh, spacing: f32) -> Result<(Vertex, [usize; 3])> {
+    if !(spacing.is_finite() && spacing > 0.0) {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("voxel spacing must be positive and finite, got {}", spacing),
+        ));
+    }
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for v in mesh.faces.iter().flat_map(|f| f.vertices) {
+        for c in 0..3 {
+            min[c] = min[c].min(mesh.vertices[v][c]);
+            max[c] = max[c].max(mesh.vertices[v][c]);
+        }
+    }
+    if mesh.faces.is_empty() {
+        return Ok((Vertex::new([0.0; 3]), [0; 3]));
+    }
+    let too_large = || {
+        ::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("too many voxels of size {} to cover the mesh", spacing),
+        )
+    };
+    let mut dimensions = [0; 3];
+    for c in 0..3 {
+        let cells = ((max[c] - min[c]) / spacing).floor();
+        // NaN comes from non-finite vertices.
+        if cells.is_nan() || cells >= usize::MAX as f32 {
+            return Err(too_large());
+        }
+        dimensions[c] = cells as usize + 1;
+    }
+    dimensions
+        .iter()
+        .try_fold(1usize, |product, &d| product.checked_mul(d))
+        .ok_or_else(too_large)?;
+    Ok((Vertex::new(min), dimensions))
+}
+
+// Calls set for every voxel that is set, possibly more than once.
+fn visit_voxels<F>(
+    mesh: &IndexedMesh,
+    origin: Vertex,
+    spacing: f32,
+    dimensions: [usize; 3],
+    fill: VoxelFill,
+    mut set: F,
+) where
+    F: FnMut([usize; 3]),
+{
+    let cell = |p: f32, c: usize| {
+        (((p - origin[c]) / spacing).floor().max(0.0) as usize).min(dimensions[c] - 1)
+    };
+    for face in &mesh.faces {
+        let triangle = face.vertices.map(|i| mesh.vertices[i]);
+        let range = |c: usize| {
+            let values = triangle.map(|v| v[c]);
+            let lo = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let hi = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            // Faces on a voxel boundary touch the voxels on both sides.
+            cell(lo, c).saturating_sub(1)..=cell(hi, c)
+        };
+        for z in range(2) {
+            for y in range(1) {
+                for x in range(0) {
+                    let voxel = [x, y, z];
+                    let min = Vertex::new([0, 1, 2].map(|c| origin[c] + voxel[c] as f32 * spacing));
+                    let max =
+                        Vertex::new([0, 1, 2].map(|c| origin[c] + (voxel[c] + 1) as f32 * spacing));
+                    if triangle_box_overlap(&triangle, min, max) {
+                        set(voxel);
+                    }
+                }
+            }
+        }
+    }
+    if fill == VoxelFill::Surface {
+        return;
+    }
+
+    // Cast a ray up every column of voxel centers and count the crossings of the surface, +1
+    // where it enters the mesh through the front of a face and -1 where it leaves. Faces meeting
+    // at a crossing, like at an edge, count once per direction.
+    let bvh = Bvh::new(mesh);
+    let start = origin[2] - spacing;
+    for y in 0..dimensions[1] {
+        for x in 0..dimensions[0] {
+            let bottom = voxel_center(origin, spacing, [x, y, 0]);
+            let ray = Ray {
+                origin: Vertex::new([bottom[0], bottom[1], start]),
+                direction: Vector::new([0.0, 0.0, 1.0]),
+            };
+            let hits = bvh.all_hits(&ray);
+            if hits.is_empty() {
+                continue;
+            }
+            let mut crossings = Vec::new();
+            let mut depths = vec![0];
+            let mut k = 0;
+            while k < hits.len() {
+                let distance = hits[k].distance;
+                let (mut entering, mut leaving) = (false, false);
+                while k < hits.len() && hits[k].distance == distance {
+                    if hits[k].back_face {
+                        leaving = true;
+                    } else {
+                        entering = true;
+                    }
+                    k += 1;
+                }
+                crossings.push(start + distance);
+                depths.push(depths[depths.len() - 1] + i32::from(entering) - i32::from(leaving));
+            }
+            // A ray that does not leave the mesh as often as it enters passed through a hole, its
+            // column is classified by winding numbers instead, once per interval.
+            let closed = depths[depths.len() - 1] == 0;
+            let mut interval = None;
+            let mut inside = false;
+            for z in 0..dimensions[2] {
+                let center = voxel_center(origin, spacing, [x, y, z]);
+                let current = crossings.partition_point(|&c| c < center[2]);
+                if crossings.get(current) == Some(&center[2]) {
+                    // Centers on the surface are in touching voxels anyway and would be
+                    // ambiguous to classify.
+                    continue;
+                }
+                if interval != Some(current) {
+                    interval = Some(current);
+                    inside = if closed {
+                        depths[current] > 0
+                    } else {
+                        bvh.winding_number(center) >= 0.5
+                    };
+                }
+                if inside {
+                    set([x, y, z]);
+                }
+            }
+        }
+    }
+}
@@ -20,19 +20,67 @@ where
     I: ::std::iter::ExactSizeIterator<Item = T>,
     T: std::borrow::Borrow<Triangle>,
 {
+    write_stl_with_header(writer, &[], mesh)
+}
+
+/// Like [write_stl](fn.write_stl.html), but fills the 80 byte header with `header` instead of
+/// zeros, e.g. to record the [Units](enum.Units.html) of the mesh.
+/// Fails with `InvalidInput` if the header is longer than 80 bytes, or if it starts with
+/// `solid ` or the signature of gzip or zstd, which would make readers take the file for ascii
+/// or compressed STL.
+///
+/// ```
+/// use stl_io::{Vertex, Normal, Units};
+/// let mesh = [stl_io::Triangle { normal: Normal::new([1.0, 0.0, 0.0]),
+///                                vertices: [Vertex::new([0.0, -1.0, 0.0]),
+///                                           Vertex::new([0.0, 1.0, 0.0]),
+///                                           Vertex::new([0.0, 0.0, 0.5])]}];
+/// let mut binary_stl = Vec::<u8>::new();
+/// let header = Units::Inches.header_text();
+/// stl_io::write_stl_with_header(&mut binary_stl, header.as_bytes(), mesh.iter()).unwrap();
+/// let mut reader = ::std::io::Cursor::new(binary_stl);
+/// let header = stl_io::read_stl_header(&mut reader).unwrap();
+/// assert_eq!(Units::from_text(&header), Some(Units::Inches));
+/// ```
+pub fn write_stl_with_header<T, W, I>(writer: &mut W, header: &[u8], mesh: I) -> Result<()>
+where
+    W: ::std::io::Write,
+    I: ::std::iter::ExactSizeIterator<Item = T>,
+    T: std::borrow::Borrow<Triangle>,
+{
+    if header.len() > 80 {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            format!("STL header is limited to 80 bytes, got {}", header.len()),
+        ));
+    }
+    if header.starts_with(b"solid ") {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            "binary STL header must not start with \"solid \"",
+        ));
+    }
+    if super::compression::looks_compressed(header) {
+        return Err(::std::io::Error::new(
+            ::std::io::ErrorKind::InvalidInput,
+            "binary STL header must not start like a gzip or zstd file",
+        ));
+    }
     let mut writer = BufWriter::new(writer);
 
     // Write 80 byte header
-    writer.write_all(&[0u8; 80])?;
+    let mut padded_header = [0u8; 80];
+    padded_header[..header.len()].copy_from_slice(header);
+    writer.write_all(&padded_header)?;
     writer.write_u32::<LittleEndian>(mesh.len() as u32)?;
     for t in mesh {
         let t = t.borrow();
         for f in &t.normal.0 {
-            writer.write_f32::<LittleEndian>(*f as f32)?;
+            writer.write_f32::<LittleEndian>(*f)?;
         }
         for &p in &t.vertices {
             for c in &p.0 {
-                writer.write_f32::<LittleEndian>(*c as f32)?;
+                writer.write_f32::<LittleEndian>(*c)?;
             }
         }
         // Attribute byte count
@@ -0,0 +1,169 @@
+use std::io::Result;
+
+fn invalid(message: String) -> ::std::io::Error {
+    ::std::io::Error::new(
+        ::std::io::ErrorKind::InvalidData,
+        format!("XML: {}", message),
+    )
+}
+
+// Start or end tag of a XML element.
+pub(crate) struct Element<'a> {
+    // Name without namespace prefix.
+    pub name: &'a str,
+    pub attributes: Vec<(&'a str, String)>,
+    pub end: bool,
+    pub empty: bool,
+}
+
+impl Element<'_> {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn required<T: ::std::str::FromStr>(&self, name: &str) -> Result<T> {
+        let value = self
+            .attribute(name)
+            .ok_or_else(|| invalid(format!("<{}> without {} attribute", self.name, name)))?;
+        value
+            .trim()
+            .parse()
+            .map_err(|_| invalid(format!("invalid {} of <{}>: {:?}", name, self.name, value)))
+    }
+}
+
+// Minimal pull parser for the XML based mesh formats. It skips declarations, comments and
+// text that is not asked for, and does not check that the elements are properly nested.
+pub(crate) struct XmlReader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> XmlReader<'a> {
+    pub fn new(xml: &'a str) -> XmlReader<'a> {
+        XmlReader { rest: xml }
+    }
+
+    // Text up to the next tag, trimmed and with entities replaced.
+    pub fn text(&mut self) -> Result<String> {
+        let end = self.rest.find('<').unwrap_or(self.rest.len());
+        let text = unescape(self.rest[..end].trim())?;
+        self.rest = &self.rest[end..];
+        Ok(text)
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<()> {
+        let pos = self
+            .rest
+            .find(end)
+            .ok_or_else(|| invalid(format!("unterminated XML, expected {:?}", end)))?;
+        self.rest = &self.rest[pos + end.len()..];
+        Ok(())
+    }
+
+    pub fn next_element(&mut self) -> Result<Option<Element<'a>>> {
+        loop {
+            match self.rest.find('<') {
+                None => return Ok(None),
+                Some(pos) => self.rest = &self.rest[pos..],
+            }
+            if self.rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if self.rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return self.parse_tag().map(Some);
+            }
+        }
+    }
+
+    fn parse_tag(&mut self) -> Result<Element<'a>> {
+        let end = self.rest.starts_with("</");
+        let rest = &self.rest[if end { 2 } else { 1 }..];
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .ok_or_else(|| invalid("unterminated XML tag".to_string()))?;
+        let name = &rest[..name_end];
+        let mut element = Element {
+            name: name.rsplit(':').next().unwrap_or(name),
+            attributes: Vec::new(),
+            end,
+            empty: false,
+        };
+        let mut rest = &rest[name_end..];
+        loop {
+            rest = rest.trim_start();
+            if let Some(r) = rest.strip_prefix("/>") {
+                element.empty = true;
+                self.rest = r;
+                return Ok(element);
+            }
+            if let Some(r) = rest.strip_prefix('>') {
+                self.rest = r;
+                return Ok(element);
+            }
+            let eq = rest
+                .find('=')
+                .ok_or_else(|| invalid(format!("malformed attribute in <{}>", name)))?;
+            let attribute = rest[..eq].trim();
+            rest = rest[eq + 1..].trim_start();
+            let quote = rest
+                .chars()
+                .next()
+                .filter(|&c| c == '"' || c == '\'')
+                .ok_or_else(|| {
+                    invalid(format!("unquoted attribute {} in <{}>", attribute, name))
+                })?;
+            let close = rest[1..]
+                .find(quote)
+                .ok_or_else(|| invalid(format!("unterminated attribute {}", attribute)))?;
+            let value = unescape(&rest[1..close + 1])?;
+            element
+                .attributes
+                .push((attribute.rsplit(':').next().unwrap_or(attribute), value));
+            rest = &rest[close + 2..];
+        }
+    }
+}
+
+pub(crate) fn unescape(text: &str) -> Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let end = rest
+            .find(';')
+            .ok_or_else(|| invalid(format!("unterminated entity in {:?}", text)))?;
+        let entity = &rest[..end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|d| d.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        result.push(c.ok_or_else(|| invalid(format!("unknown entity &{};", entity)))?);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}